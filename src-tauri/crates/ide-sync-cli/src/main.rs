@@ -0,0 +1,679 @@
+//! Headless command-line interface for scripted syncs (cron, dotfile bootstrap, CI).
+//!
+//! Uses the same backup store, history log and retention settings as the desktop app.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ide_sync_core::backup::{self, BackupFilter};
+use ide_sync_core::config::{self, SyncPreset};
+use ide_sync_core::error::AppError;
+use ide_sync_core::groups::{self, SyncGroup};
+use ide_sync_core::history::{self, HistoryQuery};
+use ide_sync_core::scheduler::{self, JobAction, JobSchedule, ScheduledJob};
+use ide_sync_core::{ide, sync, t, watcher};
+use ide_sync_core::models::{FanoutResult, IdeProfile, SyncFilters, SyncOptions, SyncStrategy, SyncTrigger};
+use serde::Serialize;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "ide-sync-pro-cli", version, about = "Sync settings, keybindings, snippets and extensions between VS Code based IDEs")]
+struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List IDEs detected on this machine
+    ListIdes,
+    /// Sync configuration from one IDE to another
+    Sync(SyncArgs),
+    /// Inspect, restore and prune backups
+    #[command(subcommand)]
+    Backups(BackupsCommand),
+    /// Manage and run saved sync groups
+    #[command(subcommand)]
+    Groups(GroupsCommand),
+    /// Show sync history, or a single record with its full log
+    History(HistoryArgs),
+    /// Watch the sources of groups with auto sync and run them on change (until interrupted)
+    Watch,
+    /// Manage and run scheduled sync jobs
+    #[command(subcommand)]
+    Jobs(JobsCommand),
+    /// Run scheduled jobs when they are due, catching up on missed runs first (until interrupted)
+    Schedule,
+    /// Manage and run named sync presets
+    #[command(subcommand)]
+    Presets(PresetsCommand),
+}
+
+#[derive(Args)]
+struct SyncArgs {
+    /// Source IDE id (see `list-ides`)
+    #[arg(long)]
+    from: String,
+    /// Target IDE id; repeat to sync into several targets at once
+    #[arg(long, required = true)]
+    to: Vec<String>,
+    #[arg(long)]
+    settings: bool,
+    #[arg(long)]
+    keybindings: bool,
+    #[arg(long)]
+    snippets: bool,
+    #[arg(long)]
+    extensions: bool,
+    /// Sync everything (settings, keybindings, snippets and extensions)
+    #[arg(long)]
+    all: bool,
+    /// Default: the strategy chosen in the app's settings
+    #[arg(long, value_enum)]
+    strategy: Option<StrategyArg>,
+    /// Show what would change without writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StrategyArg {
+    Merge,
+    Overwrite,
+    /// Merge changes from both sides since the last bidirectional sync
+    Bidirectional,
+}
+
+impl SyncArgs {
+    /// Without any category flag, the default categories from the app's settings are synced
+    fn options(&self) -> SyncOptions {
+        let defaults = config::load_config().settings.default_options();
+        let none_selected = !(self.settings || self.keybindings || self.snippets || self.extensions);
+        SyncOptions {
+            sync_settings: self.all || self.settings || (none_selected && defaults.sync_settings),
+            sync_keybindings: self.all || self.keybindings || (none_selected && defaults.sync_keybindings),
+            sync_snippets: self.all || self.snippets || (none_selected && defaults.sync_snippets),
+            sync_extensions: self.all || self.extensions || (none_selected && defaults.sync_extensions),
+            dry_run: self.dry_run,
+            strategy: match self.strategy {
+                Some(StrategyArg::Merge) => SyncStrategy::Merge,
+                Some(StrategyArg::Overwrite) => SyncStrategy::Overwrite,
+                Some(StrategyArg::Bidirectional) => SyncStrategy::Bidirectional,
+                None => defaults.strategy,
+            },
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum BackupsCommand {
+    /// List backups of all IDEs, newest first
+    List {
+        /// IDE the backed-up file belongs to (display name, e.g. "Cursor")
+        #[arg(long)]
+        ide: Option<String>,
+        /// "settings", "keybindings", "snippets" or "extensions"
+        #[arg(long)]
+        file_type: Option<String>,
+        #[arg(long)]
+        source_ide: Option<String>,
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Restore a backup over its original file
+    Restore {
+        backup_path: String,
+    },
+    /// Delete old backups according to the retention policy
+    Prune {
+        /// Only prune backups of this file (default: every file that has backups)
+        #[arg(long)]
+        file: Option<String>,
+        /// Keep the newest N backups instead of applying the retention policy
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GroupsCommand {
+    /// List saved sync groups
+    List,
+    /// Save a group; takes the same options as `sync`
+    Create {
+        #[arg(long)]
+        name: String,
+        #[command(flatten)]
+        sync: SyncArgs,
+        #[command(flatten)]
+        filters: FilterArgs,
+        /// Run automatically when the source's files change (see `watch`)
+        #[arg(long)]
+        auto: bool,
+    },
+    /// Delete a group (its history is kept)
+    Delete {
+        /// Group id or name
+        group: String,
+    },
+    /// Sync the group's source into all of its targets
+    Run {
+        /// Group id or name
+        group: String,
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum JobsCommand {
+    /// List scheduled jobs with their next run
+    List,
+    /// Save a job that runs a group or a sync on a schedule
+    Create {
+        #[arg(long)]
+        name: String,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+        #[command(subcommand)]
+        action: JobActionArgs,
+    },
+    /// Delete a job (its history is kept)
+    Delete {
+        /// Job id or name
+        job: String,
+    },
+    /// Run a job now
+    Run {
+        /// Job id or name
+        job: String,
+    },
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct ScheduleArgs {
+    /// Run every N minutes
+    #[arg(long)]
+    every: Option<u64>,
+    /// Run at the times of a cron expression in local time (e.g. "0 9 * * 1-5")
+    #[arg(long)]
+    cron: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum JobActionArgs {
+    /// Run a saved sync group
+    Group {
+        /// Group id or name
+        group: String,
+    },
+    /// Run a saved sync preset
+    Preset {
+        name: String,
+    },
+    /// Sync like `sync` does
+    Sync(SyncArgs),
+}
+
+#[derive(Subcommand)]
+enum PresetsCommand {
+    /// List saved sync presets
+    List,
+    /// Save a preset; takes the same options as `sync` with a single `--to`
+    Create {
+        #[arg(long)]
+        name: String,
+        #[command(flatten)]
+        sync: SyncArgs,
+    },
+    /// Delete a preset
+    Delete {
+        name: String,
+    },
+    /// Sync as the preset describes
+    Run {
+        name: String,
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// Setting to leave out; a trailing `*` matches a prefix (e.g. `workbench.*`). Repeatable
+    #[arg(long = "exclude-setting")]
+    exclude_settings: Vec<String>,
+    /// Extension id to leave out. Repeatable
+    #[arg(long = "exclude-extension")]
+    exclude_extensions: Vec<String>,
+    /// Snippet file name to leave out (e.g. `python.json`). Repeatable
+    #[arg(long = "exclude-snippet")]
+    exclude_snippets: Vec<String>,
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    /// Show this record with its full log
+    record_id: Option<String>,
+    /// Source or target IDE name, or target IDE id
+    #[arg(long)]
+    ide: Option<String>,
+    /// "Success" or "Failed"
+    #[arg(long)]
+    status: Option<String>,
+    /// Only runs of this sync group (id or name)
+    #[arg(long)]
+    group: Option<String>,
+    /// Only runs of this scheduled job (id or name)
+    #[arg(long)]
+    job: Option<String>,
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            if cli.json {
+                eprintln!("{}", serde_json::to_string(&e).unwrap_or_default());
+            } else {
+                eprintln!("{}", t!("cli.error", error = e));
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Returns whether the command succeeded; a sync that ran but failed is `Ok(false)`
+fn run(cli: &Cli) -> Result<bool, AppError> {
+    match &cli.command {
+        Command::ListIdes => {
+            let ides = ide::get_default_ides();
+            print(cli.json, &ides, || {
+                for i in &ides {
+                    println!("{:<20} {:<24} {}", i.id, i.name, i.config_path);
+                }
+            });
+            Ok(true)
+        }
+        Command::Sync(args) => {
+            let source = find_ide(&args.from)?;
+            let mut targets: Vec<IdeProfile> = Vec::new();
+            for id in &args.to {
+                let target = find_ide(id)?;
+                if !targets.iter().any(|t| t.id == target.id) {
+                    targets.push(target);
+                }
+            }
+            let options = args.options();
+            let record_id = history::new_record_id();
+
+            if let [target] = targets.as_slice() {
+                let result = sync::perform_sync(source.clone(), target.clone(), options.clone(), &record_id);
+                history::record_sync(&source, target, &options, &result);
+                print(cli.json, &result, || {
+                    for line in &result.log {
+                        println!("{}", line);
+                    }
+                    println!("{}", t!("cli.record", id = result.record_id));
+                });
+                return Ok(result.success);
+            }
+
+            let result = sync::perform_fanout_sync(source.clone(), targets, options.clone(), &record_id)?;
+            history::record_fanout(&source, &options, &result);
+            print_fanout(cli.json, &result);
+            Ok(result.success)
+        }
+        Command::Backups(command) => run_backups(cli.json, command),
+        Command::Groups(command) => run_groups(cli.json, command),
+        Command::Jobs(command) => run_jobs(cli.json, command),
+        Command::Presets(command) => run_presets(cli.json, command),
+        Command::Schedule => {
+            let json = cli.json;
+            let _scheduler = scheduler::start(move |run| {
+                print(json, &run, || match &run.error {
+                    Some(e) => println!("{}: {}", run.job_name, e),
+                    None => println!("{}", run_done(&run.job_name, run.success, run.record_id.as_deref())),
+                });
+            });
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::Watch => {
+            let json = cli.json;
+            let _watcher = watcher::start(move |run| {
+                print(json, &run, || match &run.error {
+                    Some(e) => println!("{}: {}", run.group_name, e),
+                    None => println!("{}", run_done(&run.group_name, run.success, run.record_id.as_deref())),
+                });
+            })?;
+            loop {
+                std::thread::park();
+            }
+        }
+        Command::History(args) => {
+            if let Some(record_id) = &args.record_id {
+                let record = history::find_record(record_id)
+                    .ok_or_else(|| AppError::RecordNotFound { id: record_id.clone() })?;
+                print(cli.json, &record, || {
+                    println!("{}  {} -> {}  {}", record.id, record.source_name, record.target_name, record.status);
+                    println!("{}", record.details);
+                    for line in &record.log {
+                        println!("  {}", line);
+                    }
+                });
+                return Ok(true);
+            }
+            let page = history::query_history(&HistoryQuery {
+                ide: args.ide.clone(),
+                status: args.status.clone(),
+                group: args.group.clone(),
+                // Records of a deleted job can still be found by its id
+                job: args.job.as_ref().map(|j| scheduler::find_job(j).map(|job| job.id).unwrap_or_else(|_| j.clone())),
+                offset: args.offset,
+                limit: Some(args.limit),
+                ..Default::default()
+            });
+            print(cli.json, &page, || {
+                for r in &page.records {
+                    let time = chrono::DateTime::from_timestamp(r.timestamp, 0)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_default();
+                    let undone = if r.undone { format!(" {}", t!("cli.undone")) } else { String::new() };
+                    let trigger = match r.trigger {
+                        SyncTrigger::Manual => String::new(),
+                        SyncTrigger::Auto => format!(" {}", t!("cli.trigger_auto")),
+                        SyncTrigger::Scheduled => format!(" {}", t!("cli.trigger_scheduled")),
+                    };
+                    println!("{}  {}  {} -> {}  {}{}{}", r.id, time, r.source_name, r.target_name, r.status, trigger, undone);
+                }
+                println!("{}", t!("cli.records_page", shown = page.records.len(), total = page.total));
+            });
+            Ok(true)
+        }
+    }
+}
+
+fn run_backups(json: bool, command: &BackupsCommand) -> Result<bool, AppError> {
+    match command {
+        BackupsCommand::List { ide, file_type, source_ide, offset, limit } => {
+            let page = backup::list_all_backups(&BackupFilter {
+                ide: ide.clone(),
+                file_type: file_type.clone(),
+                source_ide: source_ide.clone(),
+                offset: *offset,
+                limit: *limit,
+                ..Default::default()
+            })?;
+            print(json, &page, || {
+                for b in &page.backups {
+                    println!(
+                        "{}  {:<12} {:<12} {:.3} MB  {}",
+                        b.formatted_time, b.metadata.target_ide, b.metadata.file_type, b.size_mb, b.metadata.backup_path
+                    );
+                }
+                println!("{}", t!("cli.backups_page", shown = page.backups.len(), total = page.total));
+            });
+            Ok(true)
+        }
+        BackupsCommand::Restore { backup_path } => {
+            let outcome = backup::restore_backup(backup_path, &history::new_record_id())?;
+            history::record_restore(
+                &outcome.snapshot,
+                outcome.snapshot.source_ide.clone(),
+                ide_sync_core::t!("history.restore_backup", path = backup_path),
+                vec![outcome.message.clone()],
+            );
+            print(json, &outcome, || println!("{}", outcome.message));
+            Ok(true)
+        }
+        BackupsCommand::Prune { file, keep } => {
+            let files = match file {
+                Some(file) => vec![file.clone()],
+                None => backup::backed_up_files(),
+            };
+
+            let mut pruned = Vec::new();
+            for path in files {
+                let removed = match keep {
+                    Some(keep) => backup::cleanup_old_backups(&path, *keep)?,
+                    None => backup::apply_retention(&path)?,
+                };
+                pruned.push(PrunedFile { path, removed });
+            }
+            print(json, &pruned, || {
+                for p in pruned.iter().filter(|p| p.removed > 0) {
+                    println!("{:>4}  {}", p.removed, p.path);
+                }
+                println!("{}", t!("cli.backups_removed", count = pruned.iter().map(|p| p.removed).sum::<usize>()));
+            });
+            Ok(true)
+        }
+    }
+}
+
+fn run_groups(json: bool, command: &GroupsCommand) -> Result<bool, AppError> {
+    match command {
+        GroupsCommand::List => {
+            let groups = groups::list_groups();
+            print(json, &groups, || {
+                for g in &groups {
+                    println!("{:<20} {} -> {}  {:?}", g.name, g.source_id, g.target_ids.join(", "), g.options.strategy);
+                }
+            });
+            Ok(true)
+        }
+        GroupsCommand::Create { name, sync, filters, auto } => {
+            let group = groups::create_group(SyncGroup {
+                id: String::new(),
+                name: name.clone(),
+                source_id: sync.from.clone(),
+                target_ids: sync.to.clone(),
+                options: sync.options(),
+                filters: SyncFilters {
+                    exclude_settings: filters.exclude_settings.clone(),
+                    exclude_extensions: filters.exclude_extensions.clone(),
+                    exclude_snippets: filters.exclude_snippets.clone(),
+                },
+                auto_sync: *auto,
+                created_at: 0,
+                updated_at: 0,
+            })?;
+            print(json, &group, || println!("{}", t!("cli.group_created", name = group.name, id = group.id)));
+            Ok(true)
+        }
+        GroupsCommand::Delete { group } => {
+            let group = groups::find_group(group)?;
+            groups::delete_group(&group.id)?;
+            print(json, &group, || println!("{}", t!("cli.group_deleted", name = group.name)));
+            Ok(true)
+        }
+        GroupsCommand::Run { group, dry_run } => {
+            let mut group = groups::find_group(group)?;
+            group.options.dry_run |= *dry_run;
+            let ides = ide::get_default_ides();
+            let (source, _) = groups::resolve_ides(&group, &ides)?;
+            let record_id = history::new_record_id();
+            let result = groups::run_group(&group, &ides, &record_id)?;
+            history::record_group_run(&group, &source, SyncTrigger::Manual, &result);
+            print_fanout(json, &result);
+            Ok(result.success)
+        }
+    }
+}
+
+fn run_jobs(json: bool, command: &JobsCommand) -> Result<bool, AppError> {
+    match command {
+        JobsCommand::List => {
+            let jobs = scheduler::list_jobs();
+            print(json, &jobs, || {
+                for info in &jobs {
+                    let next = match info.next_run.and_then(|t| chrono::DateTime::from_timestamp(t, 0)) {
+                        Some(t) => t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                        None => t!("cli.job_disabled"),
+                    };
+                    let last = match info.job.last_success {
+                        Some(true) => t!("cli.success"),
+                        Some(false) => t!("cli.failed"),
+                        None => "-".to_string(),
+                    };
+                    println!("{:<20} {}", info.job.name, t!("cli.job_status", next = next, last = last));
+                }
+            });
+            Ok(true)
+        }
+        JobsCommand::Create { name, schedule, action } => {
+            let schedule = match (&schedule.every, &schedule.cron) {
+                (Some(minutes), _) => JobSchedule::Interval { minutes: *minutes },
+                (None, Some(expression)) => JobSchedule::Cron { expression: expression.clone() },
+                (None, None) => unreachable!("clap requires --every or --cron"),
+            };
+            let action = match action {
+                JobActionArgs::Group { group } => JobAction::Group { group_id: groups::find_group(group)?.id },
+                JobActionArgs::Preset { name } => JobAction::Preset { name: name.clone() },
+                JobActionArgs::Sync(sync) => JobAction::Sync {
+                    source_id: sync.from.clone(),
+                    target_ids: sync.to.clone(),
+                    options: sync.options(),
+                },
+            };
+            let job = scheduler::create_job(ScheduledJob {
+                id: String::new(),
+                name: name.clone(),
+                schedule,
+                action,
+                enabled: true,
+                last_run: None,
+                last_success: None,
+                last_error: None,
+                created_at: 0,
+                updated_at: 0,
+            })?;
+            print(json, &job, || println!("{}", t!("cli.job_created", name = job.name, id = job.id)));
+            Ok(true)
+        }
+        JobsCommand::Delete { job } => {
+            let job = scheduler::find_job(job)?;
+            scheduler::delete_job(&job.id)?;
+            print(json, &job, || println!("{}", t!("cli.job_deleted", name = job.name)));
+            Ok(true)
+        }
+        JobsCommand::Run { job } => {
+            let run = scheduler::run_job(&scheduler::find_job(job)?);
+            if let Some(e) = run.error {
+                return Err(e);
+            }
+            print(json, &run, || {
+                println!("{}", run_done(&run.job_name, run.success, run.record_id.as_deref()));
+            });
+            Ok(run.success)
+        }
+    }
+}
+
+fn run_presets(json: bool, command: &PresetsCommand) -> Result<bool, AppError> {
+    match command {
+        PresetsCommand::List => {
+            let presets = config::load_config().presets;
+            print(json, &presets, || {
+                for p in &presets {
+                    println!("{:<20} {} -> {}  {:?}", p.name, p.source_id, p.target_id, p.options.strategy);
+                }
+            });
+            Ok(true)
+        }
+        PresetsCommand::Create { name, sync } => {
+            let [target_id] = sync.to.as_slice() else {
+                return Err(AppError::InvalidPresetTargets { name: name.clone() });
+            };
+            let mut app_config = config::load_config();
+            app_config.presets.push(SyncPreset {
+                name: name.clone(),
+                source_id: sync.from.clone(),
+                target_id: target_id.clone(),
+                options: sync.options(),
+            });
+            let app_config = config::save_config(app_config)?;
+            let preset = app_config.presets.last().cloned();
+            print(json, &preset, || println!("{}", t!("cli.preset_created", name = name.trim())));
+            Ok(true)
+        }
+        PresetsCommand::Delete { name } => {
+            let preset = config::find_preset(name)?;
+            let mut app_config = config::load_config();
+            app_config.presets.retain(|p| p.name != preset.name);
+            config::save_config(app_config)?;
+            print(json, &preset, || println!("{}", t!("cli.preset_deleted", name = preset.name)));
+            Ok(true)
+        }
+        PresetsCommand::Run { name, dry_run } => {
+            let mut preset = config::find_preset(name)?;
+            preset.options.dry_run |= *dry_run;
+            let ides = ide::get_default_ides();
+            let (source, target) = config::resolve_ides(&preset, &ides)?;
+            let record_id = history::new_record_id();
+            let result = config::run_preset(&preset, &ides, &record_id)?;
+            history::record_sync(&source, &target, &preset.options, &result);
+            print(json, &result, || {
+                for line in &result.log {
+                    println!("{}", line);
+                }
+                println!("{}", t!("cli.record", id = result.record_id));
+            });
+            Ok(result.success)
+        }
+    }
+}
+
+/// One line for a finished job or group run: name, status and history record
+fn run_done(name: &str, success: bool, record_id: Option<&str>) -> String {
+    let status = if success { t!("cli.success") } else { t!("cli.failed") };
+    t!("cli.run_done", name = name, status = status, id = record_id.unwrap_or_default())
+}
+
+fn print_fanout(json: bool, result: &FanoutResult) {
+    print(json, result, || {
+        for target in &result.targets {
+            println!("== {} ==", target.target_name);
+            for line in &target.result.log {
+                println!("{}", line);
+            }
+        }
+        println!("{}", t!("cli.record", id = result.record_id));
+    });
+}
+
+#[derive(Serialize)]
+struct PrunedFile {
+    path: String,
+    removed: usize,
+}
+
+fn find_ide(id: &str) -> Result<IdeProfile, AppError> {
+    ide::get_default_ides()
+        .into_iter()
+        .find(|i| i.id == id)
+        .ok_or_else(|| AppError::IdeNotFound { id: id.to_string() })
+}
+
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce()) {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value).unwrap_or_default());
+    } else {
+        text();
+    }
+}
@@ -0,0 +1,261 @@
+use crate::backup;
+use crate::error::AppError;
+use crate::ide;
+use crate::models::{IdeProfile, IdeType, SyncOptions, SyncResult, SyncStrategy};
+use crate::sync;
+use chrono::Local;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 归档格式版本，格式不兼容时递增
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: i64,
+    /// 导出来源的 IDE 名称
+    pub source_ide: String,
+    /// 从快照导出时为快照 ID，从当前配置导出时为空
+    pub snapshot_id: Option<String>,
+    /// 归档内包含的文件，例如 "settings.json"、"snippets/rust.json"
+    pub files: Vec<String>,
+    /// 是否成功获取到扩展列表（CLI 不可用时为 false）
+    pub extensions_captured: bool,
+}
+
+/// 可移植的配置归档：gzip 压缩的 JSON 文档
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigArchive {
+    pub manifest: ArchiveManifest,
+    pub settings: Option<String>,
+    pub keybindings: Option<String>,
+    #[serde(default)]
+    pub snippets: BTreeMap<String, String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+impl ConfigArchive {
+    fn new(source_ide: &str, snapshot_id: Option<String>) -> Self {
+        ConfigArchive {
+            manifest: ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                created_at: Local::now().timestamp(),
+                source_ide: source_ide.to_string(),
+                snapshot_id,
+                files: Vec::new(),
+                extensions_captured: false,
+            },
+            settings: None,
+            keybindings: None,
+            snippets: BTreeMap::new(),
+            extensions: Vec::new(),
+        }
+    }
+
+    fn capture_extensions(&mut self, profile: Option<&IdeProfile>) {
+        if let Some(Ok(extensions)) = profile.map(sync::list_extensions) {
+            self.extensions = extensions;
+            self.manifest.extensions_captured = true;
+        }
+    }
+
+    fn finish_manifest(&mut self) {
+        let mut files = Vec::new();
+        if self.settings.is_some() {
+            files.push("settings.json".to_string());
+        }
+        if self.keybindings.is_some() {
+            files.push("keybindings.json".to_string());
+        }
+        files.extend(self.snippets.keys().map(|name| format!("snippets/{}", name)));
+        self.manifest.files = files;
+    }
+}
+
+/// 导出某个 IDE 当前的配置
+pub fn export_profile(profile: &IdeProfile, dest_path: &str) -> Result<ArchiveManifest, AppError> {
+    let mut archive = ConfigArchive::new(&profile.name, None);
+
+    archive.settings = fs::read_to_string(&profile.config_path).ok();
+    archive.keybindings = fs::read_to_string(ide::get_keybindings_path(&profile.config_path)).ok();
+    for path in ide::list_snippet_files(&profile.config_path) {
+        if let (Some(name), Ok(content)) = (file_name(&path), fs::read_to_string(&path)) {
+            archive.snippets.insert(name, content);
+        }
+    }
+    archive.capture_extensions(Some(profile));
+
+    write_archive(&mut archive, dest_path)
+}
+
+/// 导出一个快照记录的配置（即该次同步之前的状态）
+///
+/// 快照不记录扩展，扩展列表取自该 IDE 当前已安装的扩展。
+pub fn export_snapshot(snapshot_id: &str, dest_path: &str) -> Result<ArchiveManifest, AppError> {
+    let snapshot = backup::list_snapshots()
+        .into_iter()
+        .map(|info| info.snapshot)
+        .find(|s| s.id == snapshot_id)
+        .ok_or_else(|| AppError::SnapshotNotFound { id: snapshot_id.to_string() })?;
+
+    let mut archive = ConfigArchive::new(&snapshot.target_ide, Some(snapshot.id.clone()));
+
+    for file in &snapshot.files {
+        // 双向同步的快照也包含源一侧的文件，它们不属于目标 IDE 的配置
+        if snapshot.file_ide(file) != snapshot.target_ide {
+            continue;
+        }
+        // 同步前不存在的文件不属于快照记录的状态
+        let backup_path = match &file.backup_path {
+            Some(path) => path,
+            None => continue,
+        };
+        let metadata = backup::find_backup(backup_path)?;
+        let content = String::from_utf8_lossy(&backup::read_verified_content(&metadata)?).to_string();
+        match file.file_type.as_str() {
+            "settings" => archive.settings = Some(content),
+            "keybindings" => archive.keybindings = Some(content),
+            "snippets" => {
+                if let Some(name) = file_name(Path::new(&file.original_path)) {
+                    archive.snippets.insert(name, content);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let owner = ide::get_default_ides()
+        .into_iter()
+        .find(|i| i.name == snapshot.target_ide);
+    archive.capture_extensions(owner.as_ref());
+
+    write_archive(&mut archive, dest_path)
+}
+
+fn write_archive(archive: &mut ConfigArchive, dest_path: &str) -> Result<ArchiveManifest, AppError> {
+    archive.finish_manifest();
+
+    let json = serde_json::to_vec(archive).map_err(AppError::serialization)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish())
+        .map_err(AppError::serialization)?;
+    fs::write(dest_path, compressed).map_err(|e| AppError::io(dest_path, e))?;
+
+    Ok(archive.manifest.clone())
+}
+
+/// 读取并校验归档
+pub fn read_archive(archive_path: &str) -> Result<ConfigArchive, AppError> {
+    let compressed = fs::read(archive_path).map_err(|e| AppError::io(archive_path, e))?;
+    let invalid = || AppError::InvalidArchive { path: archive_path.to_string() };
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut json)
+        .map_err(|_| invalid())?;
+    let archive: ConfigArchive = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+    if archive.manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(AppError::UnsupportedArchiveVersion { version: archive.manifest.format_version });
+    }
+    for name in archive.snippets.keys() {
+        if file_name(Path::new(name)).as_deref() != Some(name.as_str()) {
+            return Err(AppError::InvalidArchiveEntry { name: name.clone() });
+        }
+    }
+
+    Ok(archive)
+}
+
+/// 导入时作为同步源的归档；路径在解包后填入
+pub fn source_profile(manifest: &ArchiveManifest) -> IdeProfile {
+    IdeProfile {
+        id: "archive".to_string(),
+        name: format!("Archive ({})", manifest.source_ide),
+        ide_type: IdeType::Archive,
+        config_path: String::new(),
+        extensions_path: String::new(),
+        executable: String::new(),
+        is_detected: true,
+    }
+}
+
+/// 将归档导入目标 IDE，走与实时同步相同的合并/覆盖流程
+///
+/// 归档先解包到临时目录，作为一个 `IdeType::Archive` 的源 IDE 交给 `perform_sync`。
+pub fn import_archive(archive_path: &str, target: IdeProfile, options: SyncOptions, record_id: &str) -> Result<SyncResult, AppError> {
+    // 归档是只读的源，不能双向同步
+    if options.strategy == SyncStrategy::Bidirectional {
+        return Err(AppError::UnsupportedStrategy { strategy: format!("{:?}", options.strategy) });
+    }
+    let archive = read_archive(archive_path)?;
+    let staging = staging_dir();
+
+    let result = stage_and_sync(&archive, &staging, target, options, record_id);
+
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn stage_and_sync(archive: &ConfigArchive, staging: &Path, target: IdeProfile, mut options: SyncOptions, record_id: &str) -> Result<SyncResult, AppError> {
+    let user_dir = staging.join("User");
+    let snippets_dir = user_dir.join("snippets");
+    fs::create_dir_all(&snippets_dir).map_err(|e| AppError::io(&snippets_dir, e))?;
+
+    let config_path = user_dir.join("settings.json");
+    let manifest_path = staging.join("extensions.json");
+
+    // 归档中没有的部分不参与同步，避免覆盖模式下清空目标
+    match &archive.settings {
+        Some(content) => write_staged(&config_path, content)?,
+        None => options.sync_settings = false,
+    }
+    match &archive.keybindings {
+        Some(content) => write_staged(&user_dir.join("keybindings.json"), content)?,
+        None => options.sync_keybindings = false,
+    }
+    for (name, content) in &archive.snippets {
+        write_staged(&user_dir.join("snippets").join(name), content)?;
+    }
+    if archive.manifest.extensions_captured {
+        let json = serde_json::to_string_pretty(&archive.extensions).map_err(AppError::serialization)?;
+        write_staged(&manifest_path, &json)?;
+    } else {
+        options.sync_extensions = false;
+    }
+
+    let source = IdeProfile {
+        config_path: config_path.to_string_lossy().to_string(),
+        extensions_path: manifest_path.to_string_lossy().to_string(),
+        ..source_profile(&archive.manifest)
+    };
+
+    Ok(sync::perform_sync(source, target, options, record_id))
+}
+
+fn staging_dir() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "ide-sync-pro-import-{}-{}",
+        std::process::id(),
+        Local::now().format("%Y%m%d%H%M%S%3f")
+    ))
+}
+
+fn write_staged(path: &Path, content: &str) -> Result<(), AppError> {
+    fs::write(path, content).map_err(|e| AppError::io(path, e))
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().and_then(|n| n.to_str()).map(|n| n.to_string())
+}
@@ -0,0 +1,1115 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::error::AppError;
+use crate::models::{FileOutcome, FileStatus};
+use crate::t;
+use crate::{ide, retention};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupMetadata {
+    pub backup_path: String,
+    pub original_path: String,
+    pub timestamp: i64,
+    pub source_ide: String,
+    pub target_ide: String,
+    pub file_type: String, // "settings" or "keybindings"
+    pub file_size: u64, // 原始内容大小
+    /// 内容的 SHA-256，对应 objects/<hash>.gz；旧版未压缩的备份为空
+    #[serde(default)]
+    pub content_hash: String,
+    /// 压缩后在磁盘上的大小（同一内容的多个备份共享）
+    #[serde(default)]
+    pub stored_size: u64,
+    /// 备份原因: "sync"（同步前）或 "pre-restore"（恢复前）
+    #[serde(default = "default_reason")]
+    pub reason: String,
+    /// 创建该备份的同步/恢复记录 ID
+    #[serde(default)]
+    pub record_id: Option<String>,
+}
+
+fn default_reason() -> String {
+    "sync".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+    pub metadata: BackupMetadata,
+    pub formatted_time: String,
+    pub size_mb: f64,
+}
+
+/// 一次同步运行涉及的全部文件，可作为整体恢复或删除
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub id: String,
+    pub timestamp: i64,
+    pub source_ide: String,
+    pub target_ide: String,
+    pub files: Vec<SnapshotFile>,
+    /// 对应的历史记录 ID
+    #[serde(default)]
+    pub record_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotFile {
+    pub original_path: String,
+    pub file_type: String,
+    /// 同步前的备份；同步前文件不存在时为 None，恢复快照时会删除该文件
+    pub backup_path: Option<String>,
+    /// 同步写入后的内容哈希，用于撤销前确认文件未再被修改
+    #[serde(default)]
+    pub synced_hash: Option<String>,
+    /// 该操作删除了文件（恢复到"同步前不存在"的状态）
+    #[serde(default)]
+    pub deleted: bool,
+    /// 文件所属的 IDE。双向同步会同时写入两侧，每个文件记录自己一侧的 IDE；
+    /// 为 None 时（旧记录）即快照的 `target_ide`
+    #[serde(default)]
+    pub ide: Option<String>,
+}
+
+impl Snapshot {
+    /// 快照中某个文件所属的 IDE
+    pub fn file_ide<'a>(&'a self, file: &'a SnapshotFile) -> &'a str {
+        file.ide.as_deref().unwrap_or(&self.target_ide)
+    }
+}
+
+/// 恢复操作的结果。恢复前的状态被记录为一个快照，因此恢复本身也可以撤销。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreOutcome {
+    pub message: String,
+    pub snapshot: Snapshot,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub snapshot: Snapshot,
+    pub formatted_time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BackupIndex {
+    pub backups: Vec<BackupMetadata>,
+    #[serde(default)]
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// 备份根目录: ~/.ide-sync-pro/backups
+pub fn get_backup_root() -> PathBuf {
+    crate::ensure_dir(crate::data_dir().join("backups"))
+}
+
+fn get_index_path() -> PathBuf {
+    get_backup_root().join("index.json")
+}
+
+/// 读取备份索引，索引不存在或损坏时返回空索引
+pub fn read_index() -> BackupIndex {
+    match fs::read_to_string(get_index_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => BackupIndex::default(),
+    }
+}
+
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// 修改索引期间持有的锁：进程内互斥锁 + index.lock 文件锁（防止 CLI 等其他进程同时写入）
+struct IndexLock {
+    _file: File,
+    _guard: MutexGuard<'static, ()>,
+}
+
+/// 获取索引写锁。持锁期间不要再调用其他会加锁的函数。
+fn lock_index() -> Result<IndexLock, AppError> {
+    let guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let lock_path = get_backup_root().join("index.lock");
+    let lock_failed = |e: std::io::Error| AppError::LockFailed {
+        path: lock_path.to_string_lossy().to_string(),
+        detail: e.to_string(),
+    };
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(lock_failed)?;
+    file.lock().map_err(lock_failed)?;
+    Ok(IndexLock { _file: file, _guard: guard })
+}
+
+/// 生成唯一 ID：毫秒级时间戳 + 进程内计数器 + UUID，例如
+/// `20241223-143000-123-7-9f1c2b7e4a8d4f0e8c3b5d6a7e9f0a1b`。时间戳和计数器用于排序，
+/// UUID 保证多个进程同时写入时也不会重复。
+///
+/// 旧版 ID 只有 `20241223-143000`，同一秒内的多次备份会互相覆盖。
+fn new_id(now: &DateTime<Local>) -> String {
+    let seq = ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}", now.format("%Y%m%d-%H%M%S-%3f"), seq, Uuid::new_v4().simple())
+}
+
+/// 从备份名称或快照 ID 中解析排序用的 (毫秒, 序号)，兼容新旧两种格式
+fn id_order(id: &str) -> (u32, u32) {
+    let parts: Vec<&str> = id.split('-').collect();
+    let millis = parts.get(2).and_then(|p| p.parse().ok()).unwrap_or(0);
+    let seq = parts.get(3).and_then(|p| p.parse().ok()).unwrap_or(0);
+    (millis, seq)
+}
+
+/// 备份的时间顺序：先按秒级时间戳，同一秒内再按名称中的毫秒和序号
+fn backup_order(metadata: &BackupMetadata) -> (i64, u32, u32) {
+    let id = metadata.backup_path.rsplit(".backup.").next().unwrap_or("");
+    let (millis, seq) = id_order(id);
+    (metadata.timestamp, millis, seq)
+}
+
+fn write_index(index: &BackupIndex) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(index).map_err(AppError::serialization)?;
+    let index_path = get_index_path();
+    fs::write(&index_path, json).map_err(|e| AppError::io(&index_path, e))
+}
+
+/// 内容存储目录: ~/.ide-sync-pro/backups/objects
+fn get_objects_dir() -> PathBuf {
+    get_backup_root().join("objects")
+}
+
+fn get_object_path(hash: &str) -> PathBuf {
+    get_objects_dir().join(format!("{}.gz", hash))
+}
+
+pub fn hash_content(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// 按内容哈希压缩保存，内容已存在时直接复用。返回 (哈希, 磁盘大小)
+fn store_object(content: &[u8]) -> Result<(String, u64), AppError> {
+    let hash = hash_content(content);
+    let object_path = get_object_path(&hash);
+
+    if !object_path.exists() {
+        let objects_dir = get_objects_dir();
+        fs::create_dir_all(&objects_dir).map_err(|e| AppError::io(&objects_dir, e))?;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content)
+            .and_then(|_| encoder.finish())
+            .and_then(|compressed| {
+                // 先写临时文件再重命名，避免留下半截的压缩块
+                let tmp_path = object_path.with_extension("gz.tmp");
+                fs::write(&tmp_path, compressed)?;
+                fs::rename(&tmp_path, &object_path)
+            })
+            .map_err(|e| AppError::io(&object_path, e))?;
+    }
+
+    let stored_size = fs::metadata(&object_path).map(|m| m.len()).unwrap_or(0);
+    Ok((hash, stored_size))
+}
+
+fn read_object(metadata: &BackupMetadata) -> Result<Vec<u8>, AppError> {
+    let compressed = fs::read(get_object_path(&metadata.content_hash))
+        .map_err(|_| AppError::BackupNotFound { path: metadata.backup_path.clone() })?;
+    let mut content = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut content)
+        .map_err(|_| AppError::BackupCorrupt { path: metadata.backup_path.clone() })?;
+    Ok(content)
+}
+
+/// 读取备份内容，兼容旧版未压缩的备份文件
+pub fn read_backup_content(metadata: &BackupMetadata) -> Result<Vec<u8>, AppError> {
+    if metadata.content_hash.is_empty() {
+        fs::read(&metadata.backup_path)
+            .map_err(|_| AppError::BackupNotFound { path: metadata.backup_path.clone() })
+    } else {
+        read_object(metadata)
+    }
+}
+
+/// 读取备份内容并校验：哈希必须与备份时记录的一致，内容必须是合法的 JSONC
+pub fn read_verified_content(metadata: &BackupMetadata) -> Result<Vec<u8>, AppError> {
+    let content = read_backup_content(metadata)?;
+    check_content(metadata, &content)?;
+    Ok(content)
+}
+
+fn check_content(metadata: &BackupMetadata, content: &[u8]) -> Result<(), AppError> {
+    let corrupt = || AppError::BackupCorrupt { path: metadata.backup_path.clone() };
+    if !metadata.content_hash.is_empty() && hash_content(content) != metadata.content_hash {
+        return Err(corrupt());
+    }
+    let text = std::str::from_utf8(content).map_err(|_| corrupt())?;
+    parse_to_serde_value(text, &ParseOptions::default())
+        .map_err(|_| AppError::InvalidJsonc { path: metadata.backup_path.clone() })?;
+    Ok(())
+}
+
+/// 没有其他索引条目引用时删除内容块
+fn remove_unreferenced_object(index: &BackupIndex, hash: &str) {
+    if hash.is_empty() || index.backups.iter().any(|b| b.content_hash == hash) {
+        return;
+    }
+    let _ = fs::remove_file(get_object_path(hash));
+}
+
+/// 将 IDE 名称转换为可用作目录名的片段，例如 "Lingma (灵码)" -> "lingma-灵码"
+fn path_segment(name: &str) -> String {
+    let segment: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let segment = segment
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if segment.is_empty() {
+        "unknown".to_string()
+    } else {
+        segment
+    }
+}
+
+/// 某个 IDE 某个配置文件的备份目录: ~/.ide-sync-pro/backups/<ide>/<file>/
+fn get_backup_dir(ide: &str, file_name: &str) -> PathBuf {
+    get_backup_root().join(path_segment(ide)).join(file_name)
+}
+
+/// 创建带时间戳的备份
+pub fn create_timestamped_backup(
+    file_path: &str,
+    source_ide: &str,
+    target_ide: &str,
+    file_type: &str,
+    reason: &str,
+) -> Result<BackupMetadata, AppError> {
+    let path = Path::new(file_path);
+    
+    if !path.exists() {
+        return Err(AppError::FileNotFound { path: file_path.to_string() });
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| AppError::InvalidPath { path: file_path.to_string() })?;
+
+    let content = fs::read(file_path).map_err(|e| AppError::io(file_path, e))?;
+
+    // 内容和原因都与最近一次备份相同则直接复用。原因不同时（例如恢复前的文件正是上次同步前备份的内容）
+    // 仍新建一条索引条目记下原因，内容对象按哈希共享，不会重复保存
+    let _lock = lock_index()?;
+    let mut index = read_index();
+    let content_hash = hash_content(&content);
+    let latest = index
+        .backups
+        .iter()
+        .filter(|b| b.original_path == file_path)
+        .max_by_key(|b| backup_order(b));
+    if let Some(latest) = latest {
+        if latest.content_hash == content_hash && latest.reason == reason {
+            return Ok(latest.clone());
+        }
+    }
+
+    let (content_hash, stored_size) = store_object(&content)?;
+
+    // 备份条目归档在 ~/.ide-sync-pro/backups/<ide>/<file>/ 下，实际内容按哈希存放在 objects/
+    let backup_dir = get_backup_dir(target_ide, file_name);
+
+    // 生成唯一的备份名称（毫秒时间戳 + 序号 + 随机后缀）
+    let timestamp = Local::now();
+    let mut backup_path;
+    loop {
+        backup_path = backup_dir
+            .join(format!("{}.backup.{}", file_name, new_id(&timestamp)))
+            .to_string_lossy()
+            .to_string();
+        if !index.backups.iter().any(|b| b.backup_path == backup_path) {
+            break;
+        }
+    }
+    
+    let metadata = BackupMetadata {
+        backup_path: backup_path.clone(),
+        original_path: file_path.to_string(),
+        timestamp: timestamp.timestamp(),
+        source_ide: source_ide.to_string(),
+        target_ide: target_ide.to_string(),
+        file_type: file_type.to_string(),
+        file_size: content.len() as u64,
+        content_hash,
+        stored_size,
+        reason: reason.to_string(),
+        record_id: None, // 快照创建时关联
+    };
+    
+    // 写入索引
+    index.backups.push(metadata.clone());
+    write_index(&index)?;
+    
+    Ok(metadata)
+}
+
+/// 迁移旧版备份：将配置文件旁边的 `<file>.backup.*` 及其 `.meta.json` 移入集中备份目录
+///
+/// 返回迁移的备份数量。没有元数据的备份文件无法确定来源，保持原样。
+pub fn migrate_legacy_backups(file_path: &str) -> Result<usize, AppError> {
+    let path = Path::new(file_path);
+    let invalid_path = || AppError::InvalidPath { path: file_path.to_string() };
+    let parent = path.parent().ok_or_else(invalid_path)?;
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(invalid_path)?;
+    let prefix = format!("{}.backup.", filename);
+
+    let entries = match fs::read_dir(parent) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(0),
+    };
+
+    let _lock = lock_index()?;
+    let mut index = read_index();
+    // 索引写入成功后才删除旧文件，中途失败时旧备份仍在原处，下次启动重新迁移
+    let mut migrated_files = Vec::new();
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let entry_name = entry_path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        if !entry_name.starts_with(&prefix) || entry_name.ends_with(".meta.json") {
+            continue;
+        }
+
+        let metadata_path = format!("{}.meta.json", entry_path.display());
+        let mut metadata = match fs::read_to_string(&metadata_path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<BackupMetadata>(&json).ok())
+        {
+            Some(metadata) => metadata,
+            None => continue,
+        };
+
+        let content = fs::read(&entry_path).map_err(|e| AppError::io(&entry_path, e))?;
+        let (content_hash, stored_size) = store_object(&content)?;
+
+        // 内容存放在 objects/ 中，backup_path 只是索引中的标识，不对应磁盘上的文件
+        let backup_key = get_backup_dir(&metadata.target_ide, filename).join(&entry_name);
+        metadata.backup_path = backup_key.to_string_lossy().to_string();
+        metadata.original_path = file_path.to_string();
+        metadata.file_size = content.len() as u64;
+        metadata.content_hash = content_hash;
+        metadata.stored_size = stored_size;
+        index.backups.retain(|b| b.backup_path != metadata.backup_path);
+        index.backups.push(metadata);
+        migrated_files.push((entry_path, metadata_path));
+    }
+
+    if migrated_files.is_empty() {
+        return Ok(0);
+    }
+    write_index(&index)?;
+    for (entry_path, metadata_path) in &migrated_files {
+        let _ = fs::remove_file(entry_path);
+        let _ = fs::remove_file(metadata_path);
+    }
+
+    Ok(migrated_files.len())
+}
+
+/// 将未压缩的备份文件转入内容存储，返回转换的数量
+pub fn migrate_plain_backups() -> Result<usize, AppError> {
+    let _lock = lock_index()?;
+    let mut index = read_index();
+    // 索引写入成功后才删除未压缩的文件
+    let mut migrated_files = Vec::new();
+
+    for metadata in index.backups.iter_mut() {
+        if !metadata.content_hash.is_empty() {
+            continue;
+        }
+        let content = match fs::read(&metadata.backup_path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let (content_hash, stored_size) = store_object(&content)?;
+        metadata.content_hash = content_hash;
+        metadata.stored_size = stored_size;
+        migrated_files.push(metadata.backup_path.clone());
+    }
+
+    if migrated_files.is_empty() {
+        return Ok(0);
+    }
+    write_index(&index)?;
+    for path in &migrated_files {
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(migrated_files.len())
+}
+
+fn format_timestamp(timestamp: i64) -> String {
+    let dt = DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+    let local_dt: DateTime<Local> = dt.into();
+    local_dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn to_backup_info(metadata: BackupMetadata) -> BackupInfo {
+    BackupInfo {
+        formatted_time: format_timestamp(metadata.timestamp),
+        size_mb: metadata.file_size as f64 / 1024.0 / 1024.0,
+        metadata,
+    }
+}
+
+/// 获取所有备份列表
+pub fn list_backups(file_path: &str) -> Result<Vec<BackupInfo>, AppError> {
+    // 先接管旧版遗留在配置文件旁边的备份
+    if let Err(e) = migrate_legacy_backups(file_path).and_then(|_| migrate_plain_backups()) {
+        eprintln!("{}", t!("warn.legacy_migration_failed", error = e));
+    }
+
+    let mut backups: Vec<BackupInfo> = read_index()
+        .backups
+        .into_iter()
+        .filter(|b| b.original_path == file_path)
+        .map(to_backup_info)
+        .collect();
+    
+    // 按时间倒序排列
+    backups.sort_by_key(|b| std::cmp::Reverse(backup_order(&b.metadata)));
+    
+    Ok(backups)
+}
+
+/// 按备份路径查找索引中的元数据
+pub fn find_backup(backup_path: &str) -> Result<BackupMetadata, AppError> {
+    read_index()
+        .backups
+        .into_iter()
+        .find(|b| b.backup_path == backup_path)
+        .ok_or_else(|| AppError::BackupNotFound { path: backup_path.to_string() })
+}
+
+/// 恢复前先为当前文件创建 "pre-restore" 备份，文件不存在时返回 None
+fn backup_before_restore(original_path: &str, ide: &str, file_type: &str) -> Result<Option<String>, AppError> {
+    if !Path::new(original_path).exists() {
+        return Ok(None);
+    }
+    let metadata = create_timestamped_backup(original_path, ide, ide, file_type, "pre-restore")?;
+    Ok(Some(metadata.backup_path))
+}
+
+/// 恢复备份，`record_id` 为本次恢复的历史记录 ID
+pub fn restore_backup(backup_path: &str, record_id: &str) -> Result<RestoreOutcome, AppError> {
+    // 从索引读取元数据获取原始路径
+    let metadata = find_backup(backup_path)?;
+    let content = read_verified_content(&metadata)?;
+    
+    let original_path = &metadata.original_path;
+    let pre_restore = backup_before_restore(original_path, &metadata.target_ide, &metadata.file_type)?;
+    
+    // 恢复文件
+    fs::write(original_path, &content).map_err(|e| AppError::io(original_path, e))?;
+    
+    let snapshot = create_snapshot(&metadata.source_ide, &metadata.target_ide, vec![SnapshotFile {
+        original_path: original_path.clone(),
+        file_type: metadata.file_type.clone(),
+        backup_path: pre_restore,
+        synced_hash: Some(hash_content(&content)),
+        deleted: false,
+        ide: Some(metadata.target_ide.clone()),
+    }], record_id)?;
+    
+    Ok(RestoreOutcome {
+        message: t!("restore.backup_done", path = original_path),
+        snapshot,
+    })
+}
+
+/// 删除备份。引用该备份的快照无法再恢复，一并删除。
+pub fn delete_backup(backup_path: &str) -> Result<String, AppError> {
+    let backup = Path::new(backup_path);
+    let _lock = lock_index()?;
+    let mut index = read_index();
+    let removed: Vec<BackupMetadata> = index
+        .backups
+        .iter()
+        .filter(|b| b.backup_path == backup_path)
+        .cloned()
+        .collect();
+    
+    if !backup.exists() && removed.is_empty() {
+        return Err(AppError::BackupNotFound { path: backup_path.to_string() });
+    }
+    
+    // 删除旧版未压缩的备份文件（如果存在）
+    if backup.exists() {
+        fs::remove_file(backup_path).map_err(|e| AppError::io(backup_path, e))?;
+    }
+    
+    // 从索引中移除，并清理不再被引用的内容块
+    if !removed.is_empty() {
+        index.backups.retain(|b| b.backup_path != backup_path);
+        index
+            .snapshots
+            .retain(|s| !s.files.iter().any(|f| f.backup_path.as_deref() == Some(backup_path)));
+        write_index(&index)?;
+        for metadata in &removed {
+            remove_unreferenced_object(&index, &metadata.content_hash);
+        }
+    }
+    
+    Ok(t!("backup.deleted"))
+}
+
+/// 记录一次同步运行的快照，并将本次运行创建的备份关联到历史记录 `record_id`
+pub fn create_snapshot(
+    source_ide: &str,
+    target_ide: &str,
+    files: Vec<SnapshotFile>,
+    record_id: &str,
+) -> Result<Snapshot, AppError> {
+    let _lock = lock_index()?;
+    let mut index = read_index();
+
+    let timestamp = Local::now();
+    let mut id = new_id(&timestamp);
+    while index.snapshots.iter().any(|s| s.id == id) {
+        id = new_id(&timestamp);
+    }
+    let snapshot = Snapshot {
+        id,
+        timestamp: timestamp.timestamp(),
+        source_ide: source_ide.to_string(),
+        target_ide: target_ide.to_string(),
+        files,
+        record_id: Some(record_id.to_string()),
+    };
+
+    // 内容未变化时复用的旧备份仍归属于创建它的记录
+    for backup_path in snapshot.files.iter().filter_map(|f| f.backup_path.as_ref()) {
+        let created = index
+            .backups
+            .iter_mut()
+            .find(|b| &b.backup_path == backup_path && b.record_id.is_none());
+        if let Some(metadata) = created {
+            metadata.record_id = Some(record_id.to_string());
+        }
+    }
+
+    index.snapshots.push(snapshot.clone());
+    write_index(&index)?;
+
+    Ok(snapshot)
+}
+
+/// 获取所有快照，按时间倒序
+pub fn list_snapshots() -> Vec<SnapshotInfo> {
+    let mut snapshots: Vec<SnapshotInfo> = read_index()
+        .snapshots
+        .into_iter()
+        .map(|snapshot| SnapshotInfo {
+            formatted_time: format_timestamp(snapshot.timestamp),
+            snapshot,
+        })
+        .collect();
+    snapshots.sort_by_key(|s| {
+        let (millis, seq) = id_order(&s.snapshot.id);
+        std::cmp::Reverse((s.snapshot.timestamp, millis, seq))
+    });
+    snapshots
+}
+
+fn find_snapshot(index: &BackupIndex, snapshot_id: &str) -> Result<Snapshot, AppError> {
+    index
+        .snapshots
+        .iter()
+        .find(|s| s.id == snapshot_id)
+        .cloned()
+        .ok_or_else(|| AppError::SnapshotNotFound { id: snapshot_id.to_string() })
+}
+
+/// 将快照涉及的所有文件恢复到同步前的状态，`record_id` 为本次恢复的历史记录 ID
+pub fn restore_snapshot(snapshot_id: &str, record_id: &str) -> Result<RestoreOutcome, AppError> {
+    let index = read_index();
+    let snapshot = find_snapshot(&index, snapshot_id)?;
+
+    // 先读取全部备份内容，任何一个缺失都不做改动
+    let contents = read_snapshot_contents(&index, &snapshot)?;
+
+    let mut restored_files = Vec::new();
+    for (file, content) in snapshot.files.iter().zip(contents) {
+        let exists = Path::new(&file.original_path).exists();
+        if content.is_none() && !exists {
+            continue;
+        }
+        let ide = snapshot.file_ide(file);
+        let pre_restore = backup_before_restore(&file.original_path, ide, &file.file_type)?;
+        match &content {
+            Some(content) => fs::write(&file.original_path, content)
+                .map_err(|e| AppError::io(&file.original_path, e))?,
+            // 同步前不存在的文件：删除同步创建的文件
+            None => fs::remove_file(&file.original_path)
+                .map_err(|e| AppError::io(&file.original_path, e))?,
+        }
+        restored_files.push(SnapshotFile {
+            original_path: file.original_path.clone(),
+            file_type: file.file_type.clone(),
+            backup_path: pre_restore,
+            synced_hash: content.as_ref().map(|c| hash_content(c)),
+            deleted: content.is_none(),
+            ide: Some(ide.to_string()),
+        });
+    }
+
+    let restore_snapshot = create_snapshot(&snapshot.source_ide, &snapshot.target_ide, restored_files, record_id)?;
+
+    Ok(RestoreOutcome {
+        message: t!("restore.snapshot_done", id = snapshot.id, count = snapshot.files.len()),
+        snapshot: restore_snapshot,
+    })
+}
+
+/// 将恢复生成的快照转换为历史记录中的文件结果
+pub fn snapshot_outcomes(snapshot: &Snapshot) -> Vec<FileOutcome> {
+    snapshot
+        .files
+        .iter()
+        .map(|file| FileOutcome {
+            file_type: file.file_type.clone(),
+            path: file.original_path.clone(),
+            status: if file.deleted { FileStatus::Deleted } else { FileStatus::Synced },
+            backup_path: file.backup_path.clone(),
+            message: if file.deleted { t!("file.deleted") } else { t!("file.restored") },
+            error_code: None,
+        })
+        .collect()
+}
+
+/// 读取快照中每个文件的备份内容并校验；同步前不存在的文件为 `None`
+fn read_snapshot_contents(index: &BackupIndex, snapshot: &Snapshot) -> Result<Vec<Option<Vec<u8>>>, AppError> {
+    let mut contents = Vec::new();
+    for file in &snapshot.files {
+        let content = match &file.backup_path {
+            Some(backup_path) => {
+                let metadata = index
+                    .backups
+                    .iter()
+                    .find(|b| &b.backup_path == backup_path)
+                    .ok_or_else(|| AppError::SnapshotBackupMissing { path: backup_path.clone() })?;
+                Some(read_verified_content(metadata)?)
+            }
+            None => None,
+        };
+        contents.push(content);
+    }
+    Ok(contents)
+}
+
+/// 确认快照可以撤销：涉及的文件在同步之后没有再被修改，且备份都还在、内容完好
+pub fn check_snapshot_undoable(snapshot_id: &str) -> Result<(), AppError> {
+    let index = read_index();
+    let snapshot = find_snapshot(&index, snapshot_id)?;
+    read_snapshot_contents(&index, &snapshot)?;
+
+    for file in &snapshot.files {
+        let exists = Path::new(&file.original_path).exists();
+        if file.deleted {
+            if exists {
+                return Err(AppError::ModifiedSinceSync { path: file.original_path.clone() });
+            }
+            continue;
+        }
+        let expected = file
+            .synced_hash
+            .as_ref()
+            .ok_or_else(|| AppError::MissingChecksum { path: file.original_path.clone() })?;
+        let current = fs::read(&file.original_path).map(|c| hash_content(&c)).ok();
+        if current.as_ref() != Some(expected) {
+            return Err(AppError::ModifiedSinceSync { path: file.original_path.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// 文件当前的状态是否正是本应用最近一次写入的结果（同步、恢复、撤销都记录在快照中）。
+/// 自动同步据此忽略应用自己的写入。
+pub fn is_own_write(path: &str) -> bool {
+    let index = read_index();
+    let mut snapshots: Vec<&Snapshot> = index.snapshots.iter().collect();
+    snapshots.sort_by_key(|s| {
+        let (millis, seq) = id_order(&s.id);
+        std::cmp::Reverse((s.timestamp, millis, seq))
+    });
+    let last_write = snapshots
+        .into_iter()
+        .flat_map(|s| s.files.iter())
+        .find(|f| f.original_path == path);
+
+    match (last_write, fs::read(path)) {
+        (Some(file), Ok(content)) => file.synced_hash.as_deref() == Some(hash_content(&content).as_str()),
+        (Some(file), Err(_)) => file.deleted,
+        (None, _) => false,
+    }
+}
+
+/// 删除快照及其独占的备份
+pub fn delete_snapshot(snapshot_id: &str) -> Result<String, AppError> {
+    let (snapshot, index) = {
+        let _lock = lock_index()?;
+        let mut index = read_index();
+        let snapshot = find_snapshot(&index, snapshot_id)?;
+        index.snapshots.retain(|s| s.id != snapshot_id);
+        write_index(&index)?;
+        (snapshot, index)
+    };
+
+    for backup_path in snapshot.files.iter().filter_map(|f| f.backup_path.as_ref()) {
+        let shared = index
+            .snapshots
+            .iter()
+            .any(|s| s.files.iter().any(|f| f.backup_path.as_ref() == Some(backup_path)));
+        if !shared {
+            let _ = delete_backup(backup_path);
+        }
+    }
+
+    Ok(t!("snapshot.deleted"))
+}
+
+/// 清理旧备份，保留指定数量
+pub fn cleanup_old_backups(file_path: &str, keep_count: usize) -> Result<usize, AppError> {
+    let mut backups = list_backups(file_path)?;
+    
+    let mut deleted_count = 0;
+    
+    // 保留最新的 keep_count 个备份，删除其余的
+    if backups.len() > keep_count {
+        for backup in backups.drain(keep_count..) {
+            if delete_backup(&backup.metadata.backup_path).is_ok() {
+                deleted_count += 1;
+            }
+        }
+    }
+    
+    Ok(deleted_count)
+}
+
+/// 预览按保留策略将被删除的备份（全局策略，或该文件所属 IDE 的策略）
+pub fn preview_cleanup(file_path: &str) -> Result<Vec<BackupInfo>, AppError> {
+    let backups = list_backups(file_path)?;
+    let config = retention::load_config();
+    let owner = ide::find_ide_for_path(file_path);
+    let policy = config.policy_for(owner.as_ref().map(|i| i.id.as_str()));
+    Ok(retention::select_expired(&backups, policy, Local::now()))
+}
+
+/// 按保留策略清理备份，返回删除的数量
+pub fn apply_retention(file_path: &str) -> Result<usize, AppError> {
+    let mut deleted_count = 0;
+    for backup in preview_cleanup(file_path)? {
+        delete_backup(&backup.metadata.backup_path)?;
+        deleted_count += 1;
+    }
+    Ok(deleted_count)
+}
+
+/// 索引中有备份的所有原始文件（设置、快捷键、代码片段和扩展清单），按路径排序
+pub fn backed_up_files() -> Vec<String> {
+    let paths: BTreeSet<String> = read_index()
+        .backups
+        .into_iter()
+        .map(|b| b.original_path)
+        .collect();
+    paths.into_iter().collect()
+}
+
+/// 全局备份浏览的筛选条件，所有字段均可省略
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct BackupFilter {
+    /// 备份所属的 IDE（即被覆盖文件所在的目标 IDE）
+    pub ide: Option<String>,
+    pub file_type: Option<String>,
+    pub source_ide: Option<String>,
+    /// 时间范围（Unix 秒，含两端）
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub offset: usize,
+    /// 每页数量，省略时返回全部
+    pub limit: Option<usize>,
+}
+
+impl BackupFilter {
+    fn matches(&self, metadata: &BackupMetadata) -> bool {
+        self.ide.as_ref().is_none_or(|ide| &metadata.target_ide == ide)
+            && self.file_type.as_ref().is_none_or(|t| &metadata.file_type == t)
+            && self.source_ide.as_ref().is_none_or(|ide| &metadata.source_ide == ide)
+            && self.from.is_none_or(|from| metadata.timestamp >= from)
+            && self.to.is_none_or(|to| metadata.timestamp <= to)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdeBackupStats {
+    pub ide: String,
+    pub stats: BackupStats,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupPage {
+    pub backups: Vec<BackupInfo>,
+    /// 满足筛选条件的备份总数（分页前）
+    pub total: usize,
+    /// 按 IDE 汇总的统计，基于分页前的筛选结果
+    pub per_ide: Vec<IdeBackupStats>,
+}
+
+/// 列出所有 IDE、所有文件的备份，支持筛选和分页
+pub fn list_all_backups(filter: &BackupFilter) -> Result<BackupPage, AppError> {
+    // 旧版备份散落在各个配置文件旁边，逐个接管
+    for profile in ide::get_default_ides() {
+        let keybindings_path = ide::get_keybindings_path(&profile.config_path);
+        let migrated = migrate_legacy_backups(&profile.config_path)
+            .and_then(|_| migrate_legacy_backups(&keybindings_path.to_string_lossy()));
+        if let Err(e) = migrated {
+            eprintln!("{}", t!("warn.legacy_migration_failed_for", ide = profile.name, error = e));
+        }
+    }
+    if let Err(e) = migrate_plain_backups() {
+        eprintln!("{}", t!("warn.legacy_migration_failed", error = e));
+    }
+
+    let mut backups: Vec<BackupInfo> = read_index()
+        .backups
+        .into_iter()
+        .filter(|b| filter.matches(b))
+        .map(to_backup_info)
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(backup_order(&b.metadata)));
+
+    let mut ides: Vec<String> = backups.iter().map(|b| b.metadata.target_ide.clone()).collect();
+    ides.sort();
+    ides.dedup();
+    let per_ide = ides
+        .into_iter()
+        .map(|ide| {
+            let owned: Vec<&BackupInfo> = backups.iter().filter(|b| b.metadata.target_ide == ide).collect();
+            IdeBackupStats { stats: compute_stats(&owned), ide }
+        })
+        .collect();
+
+    let total = backups.len();
+    let backups = backups
+        .into_iter()
+        .skip(filter.offset)
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(BackupPage { backups, total, per_ide })
+}
+
+/// 获取备份统计信息
+pub fn get_backup_stats(file_path: &str) -> Result<BackupStats, AppError> {
+    let backups = list_backups(file_path)?;
+    Ok(compute_stats(&backups.iter().collect::<Vec<_>>()))
+}
+
+/// 汇总一组按时间倒序排列的备份
+fn compute_stats(backups: &[&BackupInfo]) -> BackupStats {
+    let total_size: u64 = backups.iter().map(|b| b.metadata.file_size).sum();
+    let total_count = backups.len();
+    
+    // 相同内容只占用一份磁盘空间
+    let mut seen = HashSet::new();
+    let disk_size: u64 = backups
+        .iter()
+        .filter(|b| seen.insert(b.metadata.content_hash.clone()) || b.metadata.content_hash.is_empty())
+        .map(|b| b.metadata.stored_size)
+        .sum();
+    
+    let oldest = backups.last().map(|b| b.formatted_time.clone());
+    let newest = backups.first().map(|b| b.formatted_time.clone());
+    
+    BackupStats {
+        total_count,
+        total_size_mb: total_size as f64 / 1024.0 / 1024.0,
+        disk_size_mb: disk_size as f64 / 1024.0 / 1024.0,
+        oldest_backup: oldest,
+        newest_backup: newest,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OrphanKind {
+    /// 索引中有记录，但内容块或备份文件不存在
+    MissingData,
+    /// objects/ 中的内容块没有任何索引条目引用
+    UnreferencedData,
+    /// 快照引用的备份已不在索引中
+    MissingSnapshotBackup,
+    /// 旧版备份文件缺少对应的 .meta.json
+    LegacyMissingMetadata,
+    /// 旧版 .meta.json 缺少对应的备份文件
+    LegacyMissingData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CorruptBackup {
+    pub backup_path: String,
+    /// 错误码，与 `AppError` 的 code 一致
+    pub code: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrphanedItem {
+    pub path: String,
+    pub kind: OrphanKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct VerifyReport {
+    pub checked_count: usize,
+    pub corrupt: Vec<CorruptBackup>,
+    pub orphaned: Vec<OrphanedItem>,
+}
+
+/// 校验所有备份：内容哈希、JSONC 格式，以及孤立的元数据或内容块
+pub fn verify_backups() -> VerifyReport {
+    let index = read_index();
+    let mut report = VerifyReport::default();
+
+    for metadata in &index.backups {
+        report.checked_count += 1;
+        let data_exists = if metadata.content_hash.is_empty() {
+            Path::new(&metadata.backup_path).exists()
+        } else {
+            get_object_path(&metadata.content_hash).exists()
+        };
+        if !data_exists {
+            report.orphaned.push(OrphanedItem {
+                path: metadata.backup_path.clone(),
+                kind: OrphanKind::MissingData,
+            });
+            continue;
+        }
+        if let Err(e) = read_verified_content(metadata) {
+            report.corrupt.push(CorruptBackup {
+                backup_path: metadata.backup_path.clone(),
+                code: e.code().to_string(),
+                reason: e.to_string(),
+            });
+        }
+    }
+
+    // 没有被任何条目引用的内容块（包括写入中断留下的临时文件）
+    let referenced: HashSet<&str> = index.backups.iter().map(|b| b.content_hash.as_str()).collect();
+    if let Ok(entries) = fs::read_dir(get_objects_dir()) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let hash = name.strip_suffix(".gz").unwrap_or(&name);
+            if !referenced.contains(hash) {
+                report.orphaned.push(OrphanedItem {
+                    path: entry.path().to_string_lossy().to_string(),
+                    kind: OrphanKind::UnreferencedData,
+                });
+            }
+        }
+    }
+
+    for snapshot in &index.snapshots {
+        for backup_path in snapshot.files.iter().filter_map(|f| f.backup_path.as_ref()) {
+            if !index.backups.iter().any(|b| &b.backup_path == backup_path) {
+                report.orphaned.push(OrphanedItem {
+                    path: backup_path.clone(),
+                    kind: OrphanKind::MissingSnapshotBackup,
+                });
+            }
+        }
+    }
+
+    // 检测到的 IDE 目录中残留的旧版备份
+    for profile in ide::get_default_ides() {
+        let keybindings = ide::get_keybindings_path(&profile.config_path);
+        for file_path in [PathBuf::from(&profile.config_path), keybindings] {
+            scan_legacy_orphans(&file_path, &mut report.orphaned);
+        }
+    }
+
+    report
+}
+
+fn scan_legacy_orphans(file_path: &Path, orphaned: &mut Vec<OrphanedItem>) {
+    let (parent, filename) = match (file_path.parent(), file_path.file_name().and_then(|n| n.to_str())) {
+        (Some(parent), Some(filename)) => (parent, filename),
+        _ => return,
+    };
+    let prefix = format!("{}.backup.", filename);
+    let entries = match fs::read_dir(parent) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(&prefix) {
+            continue;
+        }
+        match name.strip_suffix(".meta.json") {
+            Some(data_name) => {
+                if !parent.join(data_name).exists() {
+                    orphaned.push(OrphanedItem {
+                        path: entry_path.to_string_lossy().to_string(),
+                        kind: OrphanKind::LegacyMissingData,
+                    });
+                }
+            }
+            None => {
+                if !Path::new(&format!("{}.meta.json", entry_path.display())).exists() {
+                    orphaned.push(OrphanedItem {
+                        path: entry_path.to_string_lossy().to_string(),
+                        kind: OrphanKind::LegacyMissingMetadata,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupStats {
+    pub total_count: usize,
+    pub total_size_mb: f64, // 备份内容的原始大小之和
+    pub disk_size_mb: f64,  // 去重、压缩后实际占用的磁盘空间
+    pub oldest_backup: Option<String>,
+    pub newest_backup: Option<String>,
+}
+
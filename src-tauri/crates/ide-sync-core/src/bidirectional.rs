@@ -0,0 +1,505 @@
+use crate::error::AppError;
+use crate::ide;
+use crate::models::{FileOutcome, FileStatus, IdeProfile, SyncConflict, SyncOptions, SyncResult};
+use crate::sync;
+use crate::t;
+use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 上次双向同步后两边一致的内容，作为下次三方合并的基准
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct BaseState {
+    /// 写入该状态的同步记录，撤销该记录时丢弃状态
+    record_id: String,
+    /// 键为 "settings"、"keybindings" 或 "snippets/<文件名>"
+    files: BTreeMap<String, Value>,
+}
+
+/// 基准状态目录: ~/.ide-sync-pro/sync-state
+fn get_state_dir() -> PathBuf {
+    crate::data_dir().join("sync-state")
+}
+
+/// 同一对 IDE 无论哪个作为源都共用一个基准
+fn get_state_path(a: &IdeProfile, b: &IdeProfile) -> PathBuf {
+    let (first, second) = if a.id <= b.id { (a, b) } else { (b, a) };
+    get_state_dir().join(format!("{}__{}.json", first.id, second.id))
+}
+
+fn load_base(source: &IdeProfile, target: &IdeProfile) -> BaseState {
+    fs::read_to_string(get_state_path(source, target))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_base(source: &IdeProfile, target: &IdeProfile, state: &BaseState) -> Result<(), AppError> {
+    let dir = get_state_dir();
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    let json = serde_json::to_string_pretty(state).map_err(AppError::serialization)?;
+    let path = get_state_path(source, target);
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))
+}
+
+/// 撤销双向同步后，该次同步写入的基准已不再成立，删除后下次同步按首次同步处理
+pub fn discard_base(record_id: &str) {
+    let Ok(entries) = fs::read_dir(get_state_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let written_by = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|content| serde_json::from_str::<BaseState>(&content).ok())
+            .map(|state| state.record_id);
+        if written_by.as_deref() == Some(record_id) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 三方合并的结果：两边各自应有的值，以及下次同步的基准。None 表示该键不存在。
+struct Merged {
+    source: Option<Value>,
+    target: Option<Value>,
+    base: Option<Value>,
+}
+
+impl Merged {
+    fn same(value: Option<&Value>) -> Self {
+        Merged { source: value.cloned(), target: value.cloned(), base: value.cloned() }
+    }
+}
+
+/// 冲突的 (设置项路径, 源的值, 目标的值)
+type Conflict = (String, Option<Value>, Option<Value>);
+
+/// 只有一边相对基准发生变化时采用该边的值（包括删除）；两边都变化时，
+/// 对象逐键合并，顶层数组（如 keybindings）按元素合并，其余情况记为冲突。
+fn merge(base: Option<&Value>, source: Option<&Value>, target: Option<&Value>, key: &str, conflicts: &mut Vec<Conflict>) -> Merged {
+    if source == target || target == base {
+        return Merged::same(source);
+    }
+    if source == base {
+        return Merged::same(target);
+    }
+    match (source, target) {
+        (Some(Value::Object(s)), Some(Value::Object(t))) => {
+            merge_objects(base.and_then(Value::as_object), s, t, key, conflicts)
+        }
+        (Some(Value::Array(s)), Some(Value::Array(t))) if key.is_empty() => {
+            let merged = Value::Array(merge_arrays(base.and_then(Value::as_array), s, t));
+            Merged::same(Some(&merged))
+        }
+        _ => {
+            conflicts.push((key.to_string(), source.cloned(), target.cloned()));
+            Merged { source: source.cloned(), target: target.cloned(), base: base.cloned() }
+        }
+    }
+}
+
+fn merge_objects(base: Option<&Map<String, Value>>, source: &Map<String, Value>, target: &Map<String, Value>, key: &str, conflicts: &mut Vec<Conflict>) -> Merged {
+    let mut keys: Vec<&String> = source.keys().collect();
+    keys.extend(target.keys().filter(|k| !source.contains_key(*k)));
+    if let Some(base) = base {
+        keys.extend(base.keys().filter(|k| !source.contains_key(*k) && !target.contains_key(*k)));
+    }
+
+    let (mut merged_source, mut merged_target, mut merged_base) = (Map::new(), Map::new(), Map::new());
+    for k in keys {
+        let path = if key.is_empty() { k.clone() } else { format!("{}/{}", key, k) };
+        let merged = merge(base.and_then(|b| b.get(k)), source.get(k), target.get(k), &path, conflicts);
+        if let Some(v) = merged.source {
+            merged_source.insert(k.clone(), v);
+        }
+        if let Some(v) = merged.target {
+            merged_target.insert(k.clone(), v);
+        }
+        if let Some(v) = merged.base {
+            merged_base.insert(k.clone(), v);
+        }
+    }
+
+    Merged {
+        source: Some(Value::Object(merged_source)),
+        target: Some(Value::Object(merged_target)),
+        base: Some(Value::Object(merged_base)),
+    }
+}
+
+/// 保留两边都有的和任一边新增的元素，去掉任一边删除的元素
+fn merge_arrays(base: Option<&Vec<Value>>, source: &[Value], target: &[Value]) -> Vec<Value> {
+    let in_base = |item: &Value| base.is_some_and(|b| b.contains(item));
+    let mut merged: Vec<Value> = source
+        .iter()
+        .filter(|item| target.contains(item) || !in_base(item))
+        .cloned()
+        .collect();
+    merged.extend(target.iter().filter(|item| !source.contains(item) && !in_base(item)).cloned());
+    merged
+}
+
+/// 读取 JSONC 文件，不存在时返回 None
+fn read_json(path: &str) -> Result<Option<(String, Value)>, AppError> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+    let value = parse_to_serde_value(&content, &ParseOptions::default())
+        .map_err(|_| AppError::InvalidJsonc { path: path.to_string() })?
+        .unwrap_or(Value::Null);
+    Ok(Some((content, value)))
+}
+
+/// 参与双向同步的一对文件
+struct PairFile {
+    file_type: &'static str,
+    state_key: String,
+    source_path: String,
+    target_path: String,
+}
+
+/// 一对文件的同步结果
+struct PairOutcome {
+    log: Vec<String>,
+    files: Vec<FileOutcome>,
+    snapshot_files: Vec<crate::backup::SnapshotFile>,
+    conflicts: Vec<SyncConflict>,
+    base: Option<Value>,
+    /// 写入失败的文件。此前已写入的一侧仍在 `snapshot_files` 中，撤销时可以恢复
+    failed: Option<(String, AppError)>,
+}
+
+fn sync_pair_file(pair: &PairFile, base: Option<&Value>, source: &IdeProfile, target: &IdeProfile, dry_run: bool) -> Result<Option<PairOutcome>, AppError> {
+    let source_file = read_json(&pair.source_path)?;
+    let target_file = read_json(&pair.target_path)?;
+
+    // 只有一边存在时直接复制原文，保留注释
+    let (source_content, target_content, base, conflicts) = match (source_file, target_file) {
+        (None, None) => return Ok(None),
+        (Some((content, value)), None) => (None, Some(content), Some(value), Vec::new()),
+        (None, Some((content, value))) => (Some(content), None, Some(value), Vec::new()),
+        (Some((_, source_value)), Some((_, target_value))) => {
+            let mut conflicts = Vec::new();
+            let merged = merge(base, Some(&source_value), Some(&target_value), "", &mut conflicts);
+            let render = |merged: Option<Value>, current: &Value| -> Result<Option<String>, AppError> {
+                match merged {
+                    Some(value) if &value != current => serde_json::to_string_pretty(&value)
+                        .map(Some)
+                        .map_err(AppError::serialization),
+                    _ => Ok(None),
+                }
+            };
+            let source_content = render(merged.source, &source_value)?;
+            let target_content = render(merged.target, &target_value)?;
+            (source_content, target_content, merged.base.or_else(|| base.cloned()), conflicts)
+        }
+    };
+
+    let file_name = Path::new(&pair.source_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut outcome = PairOutcome {
+        log: Vec::new(),
+        files: Vec::new(),
+        snapshot_files: Vec::new(),
+        conflicts: conflicts
+            .into_iter()
+            .map(|(key, source_value, target_value)| SyncConflict {
+                file_type: pair.file_type.to_string(),
+                file: file_name.clone(),
+                key,
+                source_value,
+                target_value,
+            })
+            .collect(),
+        base,
+        failed: None,
+    };
+
+    // 目标和源分别写入，各自先备份
+    let sides = [
+        (&pair.target_path, target_content, source, target),
+        (&pair.source_path, source_content, target, source),
+    ];
+    for (path, content, from, to) in sides {
+        let (status, backup_path, message) = match content {
+            None => (FileStatus::Unchanged, None, t!("file.unchanged", path = path)),
+            Some(_) if dry_run => (
+                FileStatus::DryRun,
+                None,
+                t!("file.dry_run", path = path, strategy = "Bidirectional"),
+            ),
+            Some(content) => match sync::write_with_backup(path, &content, &from.name, &to.name, pair.file_type) {
+                Ok(snapshot_file) => {
+                    let backup_path = snapshot_file.backup_path.clone();
+                    outcome.snapshot_files.push(snapshot_file);
+                    (FileStatus::Synced, backup_path, t!("file.synced", path = path, strategy = "Bidirectional"))
+                }
+                Err(e) => {
+                    outcome.failed = Some((path.clone(), e));
+                    break;
+                }
+            },
+        };
+        outcome.log.push(message.clone());
+        outcome.files.push(FileOutcome {
+            file_type: pair.file_type.to_string(),
+            path: path.clone(),
+            status,
+            backup_path,
+            message,
+            error_code: None,
+        });
+    }
+
+    Ok(Some(outcome))
+}
+
+/// 双向同步：以上次双向同步后的内容为基准，把两边各自的修改合并到对方。
+/// 两边都修改了的设置项作为冲突返回，两边各自保留原值。扩展取两边的并集。
+pub fn perform_bidirectional_sync(source: &IdeProfile, target: &IdeProfile, options: &SyncOptions, record_id: &str) -> SyncResult {
+    let started = Instant::now();
+    let mut log = Vec::new();
+    let mut success = true;
+    let mut files = Vec::new();
+    let mut snapshot_files = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut installed_extensions = Vec::new();
+    let mut source_installed_extensions = Vec::new();
+    let mut failed_extensions = Vec::new();
+
+    log.push(t!("sync.start", source = source.name, target = target.name));
+    log.push(t!("sync.strategy", strategy = format!("{:?}", options.strategy)));
+
+    let mut state = load_base(source, target);
+    if state.files.is_empty() {
+        log.push(t!("sync.bidi_first"));
+    }
+
+    let mut sections: Vec<(&str, Vec<PairFile>)> = Vec::new();
+    if options.sync_settings {
+        sections.push(("settings", vec![PairFile {
+            file_type: "settings",
+            state_key: "settings".to_string(),
+            source_path: source.config_path.clone(),
+            target_path: target.config_path.clone(),
+        }]));
+    }
+    if options.sync_keybindings {
+        sections.push(("keybindings", vec![PairFile {
+            file_type: "keybindings",
+            state_key: "keybindings".to_string(),
+            source_path: ide::get_keybindings_path(&source.config_path).to_string_lossy().to_string(),
+            target_path: ide::get_keybindings_path(&target.config_path).to_string_lossy().to_string(),
+        }]));
+    }
+    if options.sync_snippets {
+        let source_dir = ide::get_snippets_dir(&source.config_path);
+        let target_dir = ide::get_snippets_dir(&target.config_path);
+        let mut names: Vec<_> = ide::list_snippet_files(&source.config_path)
+            .into_iter()
+            .chain(ide::list_snippet_files(&target.config_path))
+            .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+            .collect();
+        names.sort();
+        names.dedup();
+        let pairs = names
+            .into_iter()
+            .map(|name| PairFile {
+                file_type: "snippets",
+                state_key: format!("snippets/{}", name.to_string_lossy()),
+                source_path: source_dir.join(&name).to_string_lossy().to_string(),
+                target_path: target_dir.join(&name).to_string_lossy().to_string(),
+            })
+            .collect();
+        sections.push(("snippets", pairs));
+    }
+
+    for (section, pairs) in sections {
+        log.push(t!(&format!("sync.section.{}", section)));
+        if section == "snippets" && pairs.is_empty() {
+            log.push(t!("sync.no_snippets"));
+        }
+        for pair in pairs {
+            let failed = match sync_pair_file(&pair, state.files.get(&pair.state_key), source, target, options.dry_run) {
+                Ok(Some(outcome)) => {
+                    log.extend(outcome.log);
+                    for conflict in &outcome.conflicts {
+                        log.push(t!("sync.conflict", file = conflict.file, key = conflict.key));
+                    }
+                    files.extend(outcome.files);
+                    snapshot_files.extend(outcome.snapshot_files);
+                    conflicts.extend(outcome.conflicts);
+                    // 只写入了一侧时不更新基准，下次同步会重新合并
+                    match (outcome.failed, outcome.base) {
+                        (Some(failed), _) => Some(failed),
+                        (None, Some(base)) => {
+                            state.files.insert(pair.state_key.clone(), base);
+                            None
+                        }
+                        (None, None) => None,
+                    }
+                }
+                Ok(None) => None,
+                Err(e) => Some((pair.target_path.clone(), e)),
+            };
+            if let Some((path, e)) = failed {
+                log.push(match pair.file_type {
+                    "snippets" => t!("sync.error.snippet", path = pair.source_path, error = e),
+                    file_type => t!(&format!("sync.error.{}", file_type), error = e),
+                });
+                files.push(FileOutcome {
+                    file_type: pair.file_type.to_string(),
+                    path,
+                    status: FileStatus::Failed,
+                    backup_path: None,
+                    message: e.to_string(),
+                    error_code: Some(e.code().to_string()),
+                });
+                success = false;
+            }
+        }
+    }
+
+    if options.sync_extensions {
+        log.push(t!("sync.section.extensions"));
+        if options.dry_run {
+            log.push(t!("sync.extensions_dry_run"));
+        } else {
+            match (sync::list_extensions(source), sync::list_extensions(target)) {
+                (Ok(source_extensions), Ok(target_extensions)) => {
+                    for (extensions, from, to) in [(&source_extensions, source, target), (&target_extensions, target, source)] {
+                        // 标准配置一侧合并写入扩展清单，编辑器一侧通过 CLI 安装
+                        if to.uses_extension_manifest() {
+                            let result = sync::sync_extension_manifest(extensions, from, to, true);
+                            files.push(sync::file_outcome("extensions", &to.extensions_path, &result));
+                            match result {
+                                Ok((msg, file)) => {
+                                    log.push(msg);
+                                    snapshot_files.extend(file);
+                                }
+                                Err(e) => {
+                                    log.push(t!("sync.error.extensions", error = e));
+                                    success = false;
+                                }
+                            }
+                            continue;
+                        }
+                        let (installed, failed) = sync::sync_extensions_cli(extensions, from, to, &mut log);
+                        failed_extensions.extend(failed);
+                        if to.id == target.id {
+                            installed_extensions = installed;
+                        } else {
+                            source_installed_extensions = installed;
+                        }
+                    }
+                    log.push(t!("sync.extensions_done"));
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    log.push(t!("sync.error.extensions", error = e));
+                    success = false;
+                }
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        log.push(t!("sync.conflicts_summary", count = conflicts.len()));
+    }
+
+    let snapshot_id = sync::record_snapshot(&source.name, &target.name, snapshot_files, record_id, &mut log);
+
+    if !options.dry_run {
+        state.record_id = record_id.to_string();
+        if let Err(e) = save_base(source, target, &state) {
+            log.push(t!("sync.state_failed", error = e));
+        }
+    }
+
+    SyncResult {
+        record_id: record_id.to_string(),
+        success,
+        log,
+        snapshot_id,
+        installed_extensions,
+        failed_extensions,
+        files,
+        duration_ms: started.elapsed().as_millis() as u64,
+        conflicts,
+        source_installed_extensions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(base: Option<Value>, source: Value, target: Value) -> (Merged, Vec<Conflict>) {
+        let mut conflicts = Vec::new();
+        let merged = merge(base.as_ref(), Some(&source), Some(&target), "", &mut conflicts);
+        (merged, conflicts)
+    }
+
+    #[test]
+    fn changes_on_different_keys_are_merged_into_both_sides() {
+        let base = json!({ "a": 1, "b": 1 });
+        let (merged, conflicts) = run(Some(base), json!({ "a": 2, "b": 1 }), json!({ "a": 1, "b": 3 }));
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.source, Some(json!({ "a": 2, "b": 3 })));
+        assert_eq!(merged.target, merged.source);
+        assert_eq!(merged.base, merged.source);
+    }
+
+    #[test]
+    fn both_sides_changing_the_same_key_is_a_conflict() {
+        let base = json!({ "editor": { "fontSize": 12 } });
+        let (merged, conflicts) = run(Some(base), json!({ "editor": { "fontSize": 14 } }), json!({ "editor": { "fontSize": 16 } }));
+        assert_eq!(conflicts, vec![("editor/fontSize".to_string(), Some(json!(14)), Some(json!(16)))]);
+        // 冲突的键两边各自保留，基准不变，下次同步仍会报告
+        assert_eq!(merged.source, Some(json!({ "editor": { "fontSize": 14 } })));
+        assert_eq!(merged.target, Some(json!({ "editor": { "fontSize": 16 } })));
+        assert_eq!(merged.base, Some(json!({ "editor": { "fontSize": 12 } })));
+    }
+
+    #[test]
+    fn delete_on_one_side_and_modify_on_the_other_is_a_conflict() {
+        let base = json!({ "a": 1, "b": 1 });
+        let (merged, conflicts) = run(Some(base), json!({ "b": 1 }), json!({ "a": 2, "b": 1 }));
+        assert_eq!(conflicts, vec![("a".to_string(), None, Some(json!(2)))]);
+        assert_eq!(merged.source, Some(json!({ "b": 1 })));
+        assert_eq!(merged.target, Some(json!({ "a": 2, "b": 1 })));
+    }
+
+    #[test]
+    fn unchanged_side_takes_the_deletion() {
+        let base = json!({ "a": 1, "b": 1 });
+        let (merged, conflicts) = run(Some(base), json!({ "b": 1 }), json!({ "a": 1, "b": 1 }));
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.target, Some(json!({ "b": 1 })));
+    }
+
+    #[test]
+    fn without_a_base_keys_from_either_side_are_kept_and_differences_conflict() {
+        let (merged, conflicts) = run(None, json!({ "a": 1, "c": 1 }), json!({ "b": 2, "c": 2 }));
+        assert_eq!(conflicts, vec![("c".to_string(), Some(json!(1)), Some(json!(2)))]);
+        assert_eq!(merged.source, Some(json!({ "a": 1, "b": 2, "c": 1 })));
+        assert_eq!(merged.target, Some(json!({ "a": 1, "b": 2, "c": 2 })));
+    }
+
+    #[test]
+    fn keybinding_arrays_merge_additions_and_removals() {
+        let bind = |key: &str| json!({ "key": key, "command": "x" });
+        let base = json!([bind("a"), bind("b")]);
+        let (merged, conflicts) = run(Some(base), json!([bind("a"), bind("b"), bind("c")]), json!([bind("b")]));
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.source, Some(json!([bind("b"), bind("c")])));
+        assert_eq!(merged.target, merged.source);
+    }
+}
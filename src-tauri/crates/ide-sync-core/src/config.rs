@@ -0,0 +1,295 @@
+use crate::error::AppError;
+use crate::models::{IdeProfile, SyncOptions, SyncResult, SyncStrategy};
+use crate::sync;
+use crate::t;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// config.json 的格式版本。格式变化时加 1，并在 [`MIGRATIONS`] 末尾追加对应的迁移。
+pub const CONFIG_VERSION: u32 = 1;
+
+/// `MIGRATIONS[i]` 把版本 i 的文档升级到版本 i + 1
+const MIGRATIONS: &[fn(Value) -> Value] = &[migrate_v0];
+
+/// 应用偏好设置。新建同步时的默认选项在桌面版和命令行中都取自这里。
+/// 备份总是创建，保留数量由 [`crate::retention`] 的策略决定，不在这里设置。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AppSettings {
+    pub auto_detect_on_startup: bool,
+    pub default_strategy: SyncStrategy,
+    pub default_sync_settings: bool,
+    pub default_sync_keybindings: bool,
+    pub default_sync_extensions: bool,
+    pub default_sync_snippets: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            auto_detect_on_startup: true,
+            default_strategy: SyncStrategy::Merge,
+            default_sync_settings: true,
+            default_sync_keybindings: true,
+            default_sync_extensions: false,
+            default_sync_snippets: false,
+        }
+    }
+}
+
+impl AppSettings {
+    /// 按默认设置生成的同步选项
+    pub fn default_options(&self) -> SyncOptions {
+        SyncOptions {
+            sync_settings: self.default_sync_settings,
+            sync_extensions: self.default_sync_extensions,
+            sync_keybindings: self.default_sync_keybindings,
+            sync_snippets: self.default_sync_snippets,
+            dry_run: false,
+            strategy: self.default_strategy.clone(),
+        }
+    }
+}
+
+/// 命名的同步预设：一个源、一个目标和同步选项，可按名称运行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncPreset {
+    pub name: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub options: SyncOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppConfig {
+    /// 写入时总是 [`CONFIG_VERSION`]
+    pub version: u32,
+    #[serde(default)]
+    pub settings: AppSettings,
+    #[serde(default)]
+    pub presets: Vec<SyncPreset>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            settings: AppSettings::default(),
+            presets: Vec::new(),
+        }
+    }
+}
+
+/// 读-改-写 config.json 期间持有
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn get_config_file_path() -> PathBuf {
+    crate::data_dir().join("config.json")
+}
+
+/// 版本 0：没有 `version` 字段的文档，即旧版前端保存在 localStorage 中的
+/// 设置对象（驼峰命名的扁平结构）。其中的 `autoBackup` 和 `backupRetentionCount`
+/// 从未生效，丢弃。
+fn migrate_v0(value: Value) -> Value {
+    let mut settings = Map::new();
+    if let Value::Object(legacy) = value {
+        for (key, value) in legacy {
+            if key == "autoBackup" || key == "backupRetentionCount" {
+                continue;
+            }
+            let mut snake = String::new();
+            for c in key.chars() {
+                if c.is_ascii_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            settings.insert(snake, value);
+        }
+    }
+    serde_json::json!({ "version": 1, "settings": settings, "presets": [] })
+}
+
+/// 把任意版本的文档升级到当前版本。比当前版本新的文档（由更新的应用写入）原样按当前格式读取。
+fn migrate(mut value: Value) -> Value {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as usize;
+    for migration in MIGRATIONS.iter().skip(version) {
+        value = migration(value);
+    }
+    value
+}
+
+fn parse(value: Value) -> Result<AppConfig, AppError> {
+    serde_json::from_value(migrate(value)).map_err(AppError::serialization)
+}
+
+/// 读取配置。文件不存在或无法解析时返回默认配置；旧版本的文件升级后写回。
+pub fn load_config() -> AppConfig {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    load_unlocked()
+}
+
+fn load_unlocked() -> AppConfig {
+    let Some(value) = fs::read_to_string(get_config_file_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    else {
+        return AppConfig::default();
+    };
+
+    let outdated = value.get("version").and_then(Value::as_u64).unwrap_or(0) < CONFIG_VERSION as u64;
+    // 无法解析时不写回，以免覆盖用户的文件
+    let Ok(config) = parse(value) else {
+        return AppConfig::default();
+    };
+    if outdated {
+        if let Err(e) = save_unlocked(&config) {
+            eprintln!("{}", t!("warn.config_migration_failed", error = e));
+        }
+    }
+    config
+}
+
+/// 检查并规范化预设：去掉名称两端空白
+fn validate(config: &mut AppConfig) -> Result<(), AppError> {
+    for i in 0..config.presets.len() {
+        let name = config.presets[i].name.trim().to_string();
+        if name.is_empty() || config.presets[..i].iter().any(|p| p.name == name) {
+            return Err(AppError::InvalidPresetName { name });
+        }
+        let preset = &mut config.presets[i];
+        preset.name = name;
+        if preset.source_id == preset.target_id {
+            return Err(AppError::InvalidPresetTargets { name: preset.name.clone() });
+        }
+    }
+    Ok(())
+}
+
+/// 保存整个配置，`version` 总是写为当前版本
+pub fn save_config(mut config: AppConfig) -> Result<AppConfig, AppError> {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    validate(&mut config)?;
+    config.version = CONFIG_VERSION;
+    save_unlocked(&config)?;
+    Ok(config)
+}
+
+fn save_unlocked(config: &AppConfig) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(config).map_err(AppError::serialization)?;
+    let path = get_config_file_path();
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))
+}
+
+/// 导入旧版前端保存在 localStorage 中的设置对象，保留已有的预设
+pub fn import_legacy_settings(legacy: Value) -> Result<AppConfig, AppError> {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut config = load_unlocked();
+    config.settings = parse(legacy)?.settings;
+    save_unlocked(&config)?;
+    Ok(config)
+}
+
+pub fn find_preset(name: &str) -> Result<SyncPreset, AppError> {
+    load_config()
+        .presets
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| AppError::PresetNotFound { name: name.to_string() })
+}
+
+/// 在 `ides` 中找到预设的源和目标 IDE
+pub fn resolve_ides(preset: &SyncPreset, ides: &[IdeProfile]) -> Result<(IdeProfile, IdeProfile), AppError> {
+    let find = |id: &String| {
+        ides.iter()
+            .find(|i| &i.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::IdeNotFound { id: id.clone() })
+    };
+    Ok((find(&preset.source_id)?, find(&preset.target_id)?))
+}
+
+/// 按预设同步，`record_id` 为这次运行的历史记录
+pub fn run_preset(preset: &SyncPreset, ides: &[IdeProfile], record_id: &str) -> Result<SyncResult, AppError> {
+    let (source, target) = resolve_ides(preset, ides)?;
+    Ok(sync::perform_sync(source, target, preset.options.clone(), record_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn legacy() -> Value {
+        json!({
+            "autoDetectOnStartup": false,
+            "autoBackup": true,
+            "backupRetentionCount": 5,
+            "defaultStrategy": "Overwrite",
+            "defaultSyncSettings": false,
+            "defaultSyncKeybindings": true,
+            "defaultSyncExtensions": true,
+            "defaultSyncSnippets": true
+        })
+    }
+
+    #[test]
+    fn legacy_settings_are_renamed_to_snake_case() {
+        let migrated = migrate(legacy());
+        assert_eq!(migrated["version"], json!(1));
+        assert_eq!(migrated["presets"], json!([]));
+        let settings = migrated["settings"].as_object().unwrap();
+        assert_eq!(settings["auto_detect_on_startup"], json!(false));
+        assert_eq!(settings["default_strategy"], json!("Overwrite"));
+        assert_eq!(settings["default_sync_snippets"], json!(true));
+        assert!(!settings.contains_key("auto_backup"));
+        assert!(!settings.contains_key("backup_retention_count"));
+    }
+
+    #[test]
+    fn migrated_config_round_trips() {
+        let config = parse(legacy()).unwrap();
+        assert!(!config.settings.auto_detect_on_startup);
+        assert_eq!(config.settings.default_strategy, SyncStrategy::Overwrite);
+        assert!(!config.settings.default_sync_settings);
+        assert!(config.settings.default_sync_extensions);
+
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["version"], json!(CONFIG_VERSION));
+        // 当前版本的文档不再迁移，读回后与保存前一致
+        assert_eq!(migrate(saved.clone()), saved);
+        assert_eq!(serde_json::to_value(parse(saved.clone()).unwrap()).unwrap(), saved);
+    }
+
+    #[test]
+    fn missing_legacy_keys_fall_back_to_defaults() {
+        let config = parse(json!({ "defaultSyncExtensions": true })).unwrap();
+        let defaults = AppSettings::default();
+        assert!(config.settings.default_sync_extensions);
+        assert_eq!(config.settings.auto_detect_on_startup, defaults.auto_detect_on_startup);
+        assert_eq!(config.settings.default_strategy, defaults.default_strategy);
+    }
+
+    #[test]
+    fn presets_with_duplicate_names_or_same_ides_are_rejected() {
+        let preset = |name: &str, target_id: &str| SyncPreset {
+            name: name.to_string(),
+            source_id: "vscode".to_string(),
+            target_id: target_id.to_string(),
+            options: AppSettings::default().default_options(),
+        };
+
+        let mut config = AppConfig { presets: vec![preset(" work ", "cursor")], ..AppConfig::default() };
+        assert!(validate(&mut config).is_ok());
+        assert_eq!(config.presets[0].name, "work");
+
+        config.presets.push(preset("work", "windsurf"));
+        assert!(matches!(validate(&mut config), Err(AppError::InvalidPresetName { .. })));
+
+        let mut config = AppConfig { presets: vec![preset("self", "vscode")], ..AppConfig::default() };
+        assert!(matches!(validate(&mut config), Err(AppError::InvalidPresetTargets { .. })));
+    }
+}
@@ -0,0 +1,236 @@
+use crate::backup;
+use crate::error::AppError;
+use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::TextDiff;
+use std::fs;
+
+/// 比较对象：当前文件，或另一个备份
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "backup_path", rename_all = "snake_case")]
+pub enum DiffAgainst {
+    Current,
+    Backup(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// 单个设置项或快捷键的变化
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyChange {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupDiff {
+    pub file_type: String,
+    pub old_label: String,
+    pub new_label: String,
+    /// 两边都是合法 JSONC 时为 true，否则只有文本差异
+    pub structured: bool,
+    pub changes: Vec<KeyChange>,
+    pub unified: String,
+}
+
+/// 比较备份与当前文件或另一个备份。备份为旧版本，`against` 为新版本。
+pub fn diff_backup(backup_path: &str, against: &DiffAgainst) -> Result<BackupDiff, AppError> {
+    let metadata = backup::find_backup(backup_path)?;
+    let old_content = String::from_utf8_lossy(&backup::read_backup_content(&metadata)?).to_string();
+
+    let (new_label, new_content) = match against {
+        DiffAgainst::Current => {
+            // 文件已被删除时视为空内容
+            let content = fs::read_to_string(&metadata.original_path).unwrap_or_default();
+            (metadata.original_path.clone(), content)
+        }
+        DiffAgainst::Backup(other_path) => {
+            let other = backup::find_backup(other_path)?;
+            let content = String::from_utf8_lossy(&backup::read_backup_content(&other)?).to_string();
+            (other.backup_path.clone(), content)
+        }
+    };
+    let old_label = metadata.backup_path.clone();
+
+    let parsed = parse_jsonc(&old_content).zip(parse_jsonc(&new_content));
+    let changes = match &parsed {
+        Some((old, new)) if metadata.file_type == "keybindings" => diff_keybindings(old, new),
+        Some((old, new)) => diff_settings(old, new),
+        None => Vec::new(),
+    };
+
+    let unified = TextDiff::from_lines(&old_content, &new_content)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_label, &new_label)
+        .to_string();
+
+    Ok(BackupDiff {
+        file_type: metadata.file_type,
+        old_label,
+        new_label,
+        structured: parsed.is_some(),
+        changes,
+        unified,
+    })
+}
+
+fn parse_jsonc(content: &str) -> Option<Value> {
+    if content.trim().is_empty() {
+        return Some(Value::Null);
+    }
+    parse_to_serde_value(content, &ParseOptions::default())
+        .ok()
+        .map(|v| v.unwrap_or(Value::Null))
+}
+
+/// settings.json：按顶层键比较（VS Code 的设置键本身已是 "editor.fontSize" 这种扁平形式）
+fn diff_settings(old: &Value, new: &Value) -> Vec<KeyChange> {
+    let empty = serde_json::Map::new();
+    let old_map = old.as_object().unwrap_or(&empty);
+    let new_map = new.as_object().unwrap_or(&empty);
+    let mut changes = Vec::new();
+
+    for (key, old_value) in old_map {
+        match new_map.get(key) {
+            None => changes.push(KeyChange {
+                key: key.clone(),
+                kind: ChangeKind::Removed,
+                old_value: Some(old_value.clone()),
+                new_value: None,
+            }),
+            Some(new_value) if new_value != old_value => changes.push(KeyChange {
+                key: key.clone(),
+                kind: ChangeKind::Modified,
+                old_value: Some(old_value.clone()),
+                new_value: Some(new_value.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (key, new_value) in new_map {
+        if !old_map.contains_key(key) {
+            changes.push(KeyChange {
+                key: key.clone(),
+                kind: ChangeKind::Added,
+                old_value: None,
+                new_value: Some(new_value.clone()),
+            });
+        }
+    }
+
+    changes
+}
+
+/// keybindings.json：以 key + when 标识一条快捷键。同一组合键常有多条
+/// （例如 `-command` 移除默认绑定后再绑定新命令），因此逐条配对：
+/// 先配对完全相同的条目，再配对 command 也相同的条目，最后按顺序配对剩下的同键条目，
+/// 配对后内容不同视为修改，配不上的视为新增或删除。
+fn diff_keybindings(old: &Value, new: &Value) -> Vec<KeyChange> {
+    let empty = Vec::new();
+    let old_items = old.as_array().unwrap_or(&empty);
+    let new_items = new.as_array().unwrap_or(&empty);
+
+    // pairs[i] 为与 old_items[i] 配对的 new_items 下标
+    let mut pairs: Vec<Option<usize>> = vec![None; old_items.len()];
+    let mut paired = vec![false; new_items.len()];
+    let passes: [fn(&Value, &Value) -> bool; 3] = [
+        |o, n| o == n,
+        |o, n| keybinding_id(o) == keybinding_id(n) && o.get("command") == n.get("command"),
+        |o, n| keybinding_id(o) == keybinding_id(n),
+    ];
+    for same in passes {
+        for (i, old_item) in old_items.iter().enumerate() {
+            if pairs[i].is_some() {
+                continue;
+            }
+            if let Some(j) = (0..new_items.len()).find(|&j| !paired[j] && same(old_item, &new_items[j])) {
+                pairs[i] = Some(j);
+                paired[j] = true;
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (old_item, pair) in old_items.iter().zip(&pairs) {
+        match pair.map(|j| &new_items[j]) {
+            None => changes.push(KeyChange {
+                key: keybinding_id(old_item),
+                kind: ChangeKind::Removed,
+                old_value: Some(old_item.clone()),
+                new_value: None,
+            }),
+            Some(new_item) if new_item != old_item => changes.push(KeyChange {
+                key: keybinding_id(old_item),
+                kind: ChangeKind::Modified,
+                old_value: Some(old_item.clone()),
+                new_value: Some(new_item.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (new_item, _) in new_items.iter().zip(&paired).filter(|(_, paired)| !**paired) {
+        changes.push(KeyChange {
+            key: keybinding_id(new_item),
+            kind: ChangeKind::Added,
+            old_value: None,
+            new_value: Some(new_item.clone()),
+        });
+    }
+
+    changes
+}
+
+fn keybinding_id(item: &Value) -> String {
+    let key = item.get("key").and_then(|k| k.as_str()).unwrap_or("");
+    match item.get("when").and_then(|w| w.as_str()) {
+        Some(when) => format!("{} when {}", key, when),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn removal_entry_on_the_same_chord_is_not_mistaken_for_the_change() {
+        let old = json!([{ "key": "ctrl+k", "command": "-foo" }, { "key": "ctrl+k", "command": "bar" }]);
+        let new = json!([{ "key": "ctrl+k", "command": "-foo" }, { "key": "ctrl+k", "command": "baz" }]);
+        let changes = diff_keybindings(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Modified);
+        assert_eq!(changes[0].old_value, Some(json!({ "key": "ctrl+k", "command": "bar" })));
+        assert_eq!(changes[0].new_value, Some(json!({ "key": "ctrl+k", "command": "baz" })));
+    }
+
+    #[test]
+    fn args_change_pairs_with_the_same_command() {
+        let old = json!([{ "key": "ctrl+k", "command": "-foo" }, { "key": "ctrl+k", "command": "bar", "args": 1 }]);
+        let new = json!([{ "key": "ctrl+k", "command": "bar", "args": 2 }, { "key": "ctrl+k", "command": "-foo" }]);
+        let changes = diff_keybindings(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new_value, Some(json!({ "key": "ctrl+k", "command": "bar", "args": 2 })));
+    }
+
+    #[test]
+    fn unpaired_entries_are_added_or_removed() {
+        let old = json!([{ "key": "ctrl+a", "command": "a" }, { "key": "ctrl+k", "command": "-foo" }]);
+        let new = json!([{ "key": "ctrl+k", "command": "-foo" }, { "key": "ctrl+k", "command": "bar" }]);
+        let changes = diff_keybindings(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().any(|c| c.kind == ChangeKind::Removed && c.key == "ctrl+a"));
+        assert!(changes.iter().any(|c| c.kind == ChangeKind::Added && c.key == "ctrl+k"));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::i18n;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+
+/// 后端返回给前端的错误
+///
+/// 序列化为 `{ code, message, params, path }`：`code` 是稳定的错误码，前端据此区分错误并
+/// 用 `params` 生成本地化文案；`message` 是按当前语言生成的文案；`path` 为涉及的文件（没有时为 null）。
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    IdeNotFound { id: String },
+    FileNotFound { path: String },
+    /// 读写文件失败（权限、磁盘空间等）
+    Io { path: String, detail: String },
+    InvalidPath { path: String },
+    InvalidJsonc { path: String },
+    /// 编辑器命令行工具不存在或无法执行
+    CliNotFound { executable: String },
+    CliFailed { executable: String, detail: String },
+    LockFailed { path: String, detail: String },
+    BackupNotFound { path: String },
+    /// 备份内容与记录的哈希不一致、无法解压或不是文本
+    BackupCorrupt { path: String },
+    SnapshotNotFound { id: String },
+    /// 快照引用的备份已被清理
+    SnapshotBackupMissing { path: String },
+    /// 文件在同步之后又被修改，拒绝撤销
+    ModifiedSinceSync { path: String },
+    /// 旧版快照没有记录同步后的哈希，无法确认文件状态
+    MissingChecksum { path: String },
+    RecordNotFound { id: String },
+    AlreadyUndone { id: String },
+    InvalidArchive { path: String },
+    UnsupportedArchiveVersion { version: u32 },
+    InvalidArchiveEntry { name: String },
+    /// 该操作不支持所选的同步策略（例如一对多同步不能使用双向模式）
+    UnsupportedStrategy { strategy: String },
+    GroupNotFound { id: String },
+    /// 同步组名称为空或与其他组重复
+    InvalidGroupName { name: String },
+    /// 同步组没有目标，或源也出现在目标中
+    InvalidGroupTargets { name: String },
+    JobNotFound { id: String },
+    /// 定时任务名称为空或与其他任务重复
+    InvalidJobName { name: String },
+    /// 定时任务的同步没有目标，或源也出现在目标中
+    InvalidJobTargets { name: String },
+    /// 无法解析的 cron 表达式，或间隔为 0
+    InvalidSchedule { schedule: String },
+    PresetNotFound { name: String },
+    /// 同步预设名称为空或与其他预设重复
+    InvalidPresetName { name: String },
+    /// 同步预设的目标不是一个，或与源相同
+    InvalidPresetTargets { name: String },
+    Serialization { detail: String },
+    Internal { detail: String },
+}
+
+impl AppError {
+    /// 文件操作失败。文件不存在时归为 `FileNotFound`。
+    pub fn io(path: impl AsRef<std::path::Path>, err: io::Error) -> Self {
+        let path = path.as_ref().to_string_lossy().to_string();
+        if err.kind() == io::ErrorKind::NotFound {
+            AppError::FileNotFound { path }
+        } else {
+            AppError::Io { path, detail: err.to_string() }
+        }
+    }
+
+    pub fn serialization(err: impl fmt::Display) -> Self {
+        AppError::Serialization { detail: err.to_string() }
+    }
+
+    pub fn internal(err: impl fmt::Display) -> Self {
+        AppError::Internal { detail: err.to_string() }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::IdeNotFound { .. } => "ide_not_found",
+            AppError::FileNotFound { .. } => "file_not_found",
+            AppError::Io { .. } => "io_error",
+            AppError::InvalidPath { .. } => "invalid_path",
+            AppError::InvalidJsonc { .. } => "invalid_jsonc",
+            AppError::CliNotFound { .. } => "cli_not_found",
+            AppError::CliFailed { .. } => "cli_failed",
+            AppError::LockFailed { .. } => "lock_failed",
+            AppError::BackupNotFound { .. } => "backup_not_found",
+            AppError::BackupCorrupt { .. } => "backup_corrupt",
+            AppError::SnapshotNotFound { .. } => "snapshot_not_found",
+            AppError::SnapshotBackupMissing { .. } => "snapshot_backup_missing",
+            AppError::ModifiedSinceSync { .. } => "modified_since_sync",
+            AppError::MissingChecksum { .. } => "missing_checksum",
+            AppError::RecordNotFound { .. } => "record_not_found",
+            AppError::AlreadyUndone { .. } => "already_undone",
+            AppError::InvalidArchive { .. } => "invalid_archive",
+            AppError::UnsupportedArchiveVersion { .. } => "unsupported_archive_version",
+            AppError::InvalidArchiveEntry { .. } => "invalid_archive_entry",
+            AppError::UnsupportedStrategy { .. } => "unsupported_strategy",
+            AppError::GroupNotFound { .. } => "group_not_found",
+            AppError::InvalidGroupName { .. } => "invalid_group_name",
+            AppError::InvalidGroupTargets { .. } => "invalid_group_targets",
+            AppError::JobNotFound { .. } => "job_not_found",
+            AppError::InvalidJobName { .. } => "invalid_job_name",
+            AppError::InvalidJobTargets { .. } => "invalid_job_targets",
+            AppError::InvalidSchedule { .. } => "invalid_schedule",
+            AppError::PresetNotFound { .. } => "preset_not_found",
+            AppError::InvalidPresetName { .. } => "invalid_preset_name",
+            AppError::InvalidPresetTargets { .. } => "invalid_preset_targets",
+            AppError::Serialization { .. } => "serialization_error",
+            AppError::Internal { .. } => "internal_error",
+        }
+    }
+
+    /// 涉及的文件路径
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            AppError::FileNotFound { path }
+            | AppError::Io { path, .. }
+            | AppError::InvalidPath { path }
+            | AppError::InvalidJsonc { path }
+            | AppError::LockFailed { path, .. }
+            | AppError::BackupNotFound { path }
+            | AppError::BackupCorrupt { path }
+            | AppError::SnapshotBackupMissing { path }
+            | AppError::ModifiedSinceSync { path }
+            | AppError::MissingChecksum { path }
+            | AppError::InvalidArchive { path } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// 生成本地化文案所需的参数
+    pub fn params(&self) -> BTreeMap<&'static str, String> {
+        let mut params = BTreeMap::new();
+        if let Some(path) = self.path() {
+            params.insert("path", path.to_string());
+        }
+        match self {
+            AppError::IdeNotFound { id }
+            | AppError::SnapshotNotFound { id }
+            | AppError::RecordNotFound { id }
+            | AppError::AlreadyUndone { id }
+            | AppError::GroupNotFound { id }
+            | AppError::JobNotFound { id } => {
+                params.insert("id", id.clone());
+            }
+            AppError::CliNotFound { executable } => {
+                params.insert("executable", executable.clone());
+            }
+            AppError::CliFailed { executable, detail } => {
+                params.insert("executable", executable.clone());
+                params.insert("detail", detail.clone());
+            }
+            AppError::Io { detail, .. }
+            | AppError::LockFailed { detail, .. }
+            | AppError::Serialization { detail }
+            | AppError::Internal { detail } => {
+                params.insert("detail", detail.clone());
+            }
+            AppError::UnsupportedArchiveVersion { version } => {
+                params.insert("version", version.to_string());
+            }
+            AppError::InvalidArchiveEntry { name }
+            | AppError::InvalidGroupName { name }
+            | AppError::InvalidGroupTargets { name }
+            | AppError::InvalidJobName { name }
+            | AppError::InvalidJobTargets { name }
+            | AppError::PresetNotFound { name }
+            | AppError::InvalidPresetName { name }
+            | AppError::InvalidPresetTargets { name } => {
+                params.insert("name", name.clone());
+            }
+            AppError::InvalidSchedule { schedule } => {
+                params.insert("schedule", schedule.clone());
+            }
+            AppError::UnsupportedStrategy { strategy } => {
+                params.insert("strategy", strategy.clone());
+            }
+            _ => {}
+        }
+        params
+    }
+}
+
+/// 文案来自 i18n 消息目录，随当前语言变化
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<(&str, String)> = self.params().into_iter().collect();
+        f.write_str(&i18n::translate(&format!("error.{}", self.code()), &args))
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("params", &self.params())?;
+        state.serialize_field("path", &self.path())?;
+        state.end()
+    }
+}
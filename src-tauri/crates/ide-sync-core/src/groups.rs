@@ -0,0 +1,156 @@
+use crate::error::AppError;
+use crate::models::{FanoutResult, IdeProfile, SyncFilters, SyncOptions, SyncStrategy};
+use crate::sync;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// 同步组：一个源 IDE、若干目标 IDE，以及每次运行使用的同步选项和过滤规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncGroup {
+    /// 创建时生成，新建时可省略
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub source_id: String,
+    pub target_ids: Vec<String>,
+    pub options: SyncOptions,
+    #[serde(default)]
+    pub filters: SyncFilters,
+    /// 源 IDE 的配置文件变化时自动运行
+    #[serde(default)]
+    pub auto_sync: bool,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct GroupStore {
+    #[serde(default)]
+    groups: Vec<SyncGroup>,
+}
+
+/// 读-改-写 groups.json 期间持有
+static GROUPS_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn get_groups_file_path() -> PathBuf {
+    crate::data_dir().join("groups.json")
+}
+
+fn load_store() -> GroupStore {
+    match fs::read_to_string(get_groups_file_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => GroupStore::default(),
+    }
+}
+
+fn save_store(store: &GroupStore) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(store).map_err(AppError::serialization)?;
+    let path = get_groups_file_path();
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))
+}
+
+pub fn list_groups() -> Vec<SyncGroup> {
+    load_store().groups
+}
+
+/// 按 ID 或名称查找
+pub fn find_group(id_or_name: &str) -> Result<SyncGroup, AppError> {
+    list_groups()
+        .into_iter()
+        .find(|g| g.id == id_or_name || g.name == id_or_name)
+        .ok_or_else(|| AppError::GroupNotFound { id: id_or_name.to_string() })
+}
+
+/// 检查并规范化组定义：去掉名称两端空白和重复的目标
+fn validate(group: &mut SyncGroup, others: &[SyncGroup]) -> Result<(), AppError> {
+    group.name = group.name.trim().to_string();
+    if group.name.is_empty() || others.iter().any(|g| g.id != group.id && g.name == group.name) {
+        return Err(AppError::InvalidGroupName { name: group.name.clone() });
+    }
+
+    let mut target_ids: Vec<String> = Vec::new();
+    for id in &group.target_ids {
+        if !target_ids.contains(id) {
+            target_ids.push(id.clone());
+        }
+    }
+    group.target_ids = target_ids;
+    if group.target_ids.is_empty() || group.target_ids.contains(&group.source_id) {
+        return Err(AppError::InvalidGroupTargets { name: group.name.clone() });
+    }
+
+    // 组按一对多同步运行，不支持双向模式
+    if group.options.strategy == SyncStrategy::Bidirectional {
+        return Err(AppError::UnsupportedStrategy { strategy: format!("{:?}", group.options.strategy) });
+    }
+    Ok(())
+}
+
+pub fn create_group(mut group: SyncGroup) -> Result<SyncGroup, AppError> {
+    let _guard = GROUPS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    group.id = Uuid::new_v4().to_string();
+    validate(&mut group, &store.groups)?;
+
+    let now = Local::now().timestamp();
+    group.created_at = now;
+    group.updated_at = now;
+    store.groups.push(group.clone());
+    save_store(&store)?;
+    Ok(group)
+}
+
+/// 按 `group.id` 替换已有的组，保留创建时间
+pub fn update_group(mut group: SyncGroup) -> Result<SyncGroup, AppError> {
+    let _guard = GROUPS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    validate(&mut group, &store.groups)?;
+
+    let existing = store
+        .groups
+        .iter_mut()
+        .find(|g| g.id == group.id)
+        .ok_or_else(|| AppError::GroupNotFound { id: group.id.clone() })?;
+    group.created_at = existing.created_at;
+    group.updated_at = Local::now().timestamp();
+    *existing = group.clone();
+    save_store(&store)?;
+    Ok(group)
+}
+
+/// 删除组定义。该组的历史记录保留。
+pub fn delete_group(group_id: &str) -> Result<(), AppError> {
+    let _guard = GROUPS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    let before = store.groups.len();
+    store.groups.retain(|g| g.id != group_id);
+    if store.groups.len() == before {
+        return Err(AppError::GroupNotFound { id: group_id.to_string() });
+    }
+    save_store(&store)
+}
+
+/// 在 `ides` 中找到组的源和目标 IDE
+pub fn resolve_ides(group: &SyncGroup, ides: &[IdeProfile]) -> Result<(IdeProfile, Vec<IdeProfile>), AppError> {
+    let find = |id: &String| {
+        ides.iter()
+            .find(|i| &i.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::IdeNotFound { id: id.clone() })
+    };
+    let source = find(&group.source_id)?;
+    let targets = group.target_ids.iter().map(find).collect::<Result<Vec<_>, _>>()?;
+    Ok((source, targets))
+}
+
+/// 把组的源同步到全部目标，`record_id` 为这次运行的分组历史记录
+pub fn run_group(group: &SyncGroup, ides: &[IdeProfile], record_id: &str) -> Result<FanoutResult, AppError> {
+    let (source, targets) = resolve_ides(group, ides)?;
+    sync::perform_filtered_fanout_sync(source, targets, group.options.clone(), &group.filters, record_id)
+}
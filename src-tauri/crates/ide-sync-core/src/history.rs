@@ -0,0 +1,535 @@
+use crate::backup;
+use crate::error::AppError;
+use crate::groups::SyncGroup;
+use crate::ide;
+use crate::models::{FanoutResult, IdeProfile, SyncOptions, SyncRecord, SyncResult, SyncTrigger, TargetRecord};
+use crate::retention;
+use crate::t;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// 旧版历史文件（整体读写的 JSON 数组，最多 50 条），现在只读
+pub fn get_history_file_path() -> PathBuf {
+    crate::data_dir().join("history.json")
+}
+
+/// 追加写入的历史日志，每行一个条目
+pub fn get_log_file_path() -> PathBuf {
+    crate::data_dir().join("history.jsonl")
+}
+
+/// 上次压缩以来追加的记录数，用于决定何时压缩，不必每次追加都读取整个日志
+fn get_count_file_path() -> PathBuf {
+    crate::data_dir().join("history.count")
+}
+
+/// 日志中的一行。记录写入后不再修改，状态变化以新条目追加。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LogEntry {
+    Record(Box<SyncRecord>),
+    Undone { id: String },
+}
+
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 写入日志期间持有的锁：进程内互斥锁 + history.lock 文件锁
+struct HistoryLock {
+    _file: File,
+    _guard: MutexGuard<'static, ()>,
+}
+
+fn lock_history() -> Result<HistoryLock, AppError> {
+    let guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let lock_path = crate::data_dir().join("history.lock");
+    let lock_failed = |e: std::io::Error| AppError::LockFailed {
+        path: lock_path.to_string_lossy().to_string(),
+        detail: e.to_string(),
+    };
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(lock_failed)?;
+    file.lock().map_err(lock_failed)?;
+    Ok(HistoryLock { _file: file, _guard: guard })
+}
+
+/// 调用方须持有 [`lock_history`]
+fn append_entry_locked(entry: &LogEntry) -> Result<(), AppError> {
+    let mut line = serde_json::to_string(entry).map_err(AppError::serialization)?;
+    line.push('\n');
+
+    let path = get_log_file_path();
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| AppError::io(&path, e))
+}
+
+/// 按写入顺序读取日志。写入中断留下的残缺行会被跳过。
+fn read_log() -> Vec<LogEntry> {
+    let file = match File::open(get_log_file_path()) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn read_legacy_history() -> Vec<SyncRecord> {
+    match fs::read_to_string(get_history_file_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| Vec::new()),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// 将日志条目折叠为记录列表（按时间正序），并返回找不到对应记录的撤销标记
+fn fold_log(entries: Vec<LogEntry>, records: &mut Vec<SyncRecord>) -> HashSet<String> {
+    let mut undone = HashSet::new();
+    for entry in entries {
+        match entry {
+            LogEntry::Record(record) => records.push(*record),
+            LogEntry::Undone { id } => {
+                undone.insert(id);
+            }
+        }
+    }
+    for record in records.iter_mut() {
+        if undone.contains(&record.id) {
+            record.undone = true;
+        }
+    }
+    undone
+}
+
+/// 读取全部历史（旧版 history.json + 日志），最新的在前
+pub fn read_history() -> Vec<SyncRecord> {
+    // 旧版文件中最新的在前
+    let mut records: Vec<SyncRecord> = read_legacy_history().into_iter().rev().collect();
+    fold_log(read_log(), &mut records);
+    records.reverse();
+    records
+}
+
+/// 新历史记录的 ID。在操作开始前生成，以便把备份和快照关联到该记录。
+pub fn new_record_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// 追加一条记录，超出保留策略的旧记录随之删除
+/// 追加一条记录。上次压缩以来追加的记录超过 `compaction_slack` 条时按保留策略压缩，
+/// 因此日志最多比策略多保留这么多条。
+pub fn append_history(record: SyncRecord) {
+    let policy = retention::load_config().history;
+    let appended = lock_history().and_then(|_lock| {
+        append_entry_locked(&LogEntry::Record(Box::new(record)))?;
+        if policy.keep_last == 0 && policy.keep_days == 0 {
+            return Ok(());
+        }
+        // 计数只用来安排压缩，读写失败时按 0 处理，最坏情况是推迟压缩
+        let count_path = get_count_file_path();
+        let appended = fs::read_to_string(&count_path)
+            .ok()
+            .and_then(|c| c.trim().parse::<usize>().ok())
+            .unwrap_or(0)
+            + 1;
+        if appended >= compaction_slack(&policy) {
+            compact_locked(&policy).map(|_| ())
+        } else {
+            let _ = fs::write(&count_path, appended.to_string());
+            Ok(())
+        }
+    });
+    if let Err(e) = appended {
+        eprintln!("{}", t!("warn.history_failed", error = e));
+    }
+}
+
+/// Records a sync run in history
+pub fn record_sync(source: &IdeProfile, target: &IdeProfile, options: &SyncOptions, result: &SyncResult) {
+    append_history(sync_record(source, target, options, result));
+}
+
+/// The history entry for a sync run, for callers that tag it before appending
+pub fn sync_record(source: &IdeProfile, target: &IdeProfile, options: &SyncOptions, result: &SyncResult) -> SyncRecord {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    SyncRecord {
+        id: result.record_id.clone(),
+        timestamp,
+        source_name: source.name.clone(),
+        source_id: source.id.clone(),
+        target_name: target.name.clone(),
+        status: if result.success { "Success".to_string() } else { "Failed".to_string() },
+        details: if result.success { 
+            t!("history.sync_success")
+        } else { 
+            t!("history.sync_failed", count = result.log.len())
+        },
+        target_id: target.id.clone(),
+        snapshot_id: result.snapshot_id.clone(),
+        installed_extensions: result.installed_extensions.clone(),
+        undone: false,
+        options: Some(options.clone()),
+        files: result.files.clone(),
+        failed_extensions: result.failed_extensions.clone(),
+        duration_ms: result.duration_ms,
+        log: result.log.clone(),
+        targets: Vec::new(),
+        conflicts: result.conflicts.clone(),
+        source_installed_extensions: result.source_installed_extensions.clone(),
+        group_id: None,
+        group_name: None,
+        trigger: SyncTrigger::Manual,
+        job_id: None,
+    }
+}
+
+/// Records a one-to-many sync as a single grouped entry
+pub fn record_fanout(source: &IdeProfile, options: &SyncOptions, result: &FanoutResult) {
+    append_history(fanout_record(source, options, result));
+}
+
+/// Records a sync group run; the entry is tagged with the group so it can be
+/// filtered per group, and with what started the run
+pub fn record_group_run(group: &SyncGroup, source: &IdeProfile, trigger: SyncTrigger, result: &FanoutResult) {
+    append_history(group_run_record(group, source, trigger, result));
+}
+
+/// The history entry for a sync group run, for callers that tag it further
+pub fn group_run_record(group: &SyncGroup, source: &IdeProfile, trigger: SyncTrigger, result: &FanoutResult) -> SyncRecord {
+    let mut record = fanout_record(source, &group.options, result);
+    record.group_id = Some(group.id.clone());
+    record.group_name = Some(group.name.clone());
+    record.trigger = trigger;
+    record
+}
+
+/// The grouped history entry for a one-to-many sync
+pub fn fanout_record(source: &IdeProfile, options: &SyncOptions, result: &FanoutResult) -> SyncRecord {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let failed = result.targets.iter().filter(|t| !t.result.success).count();
+
+    SyncRecord {
+        id: result.record_id.clone(),
+        timestamp,
+        source_name: source.name.clone(),
+        source_id: source.id.clone(),
+        target_name: join_names(result.targets.iter().map(|t| t.target_name.as_str())),
+        status: if result.success { "Success".to_string() } else { "Failed".to_string() },
+        details: if result.success {
+            t!("history.fanout_success", count = result.targets.len())
+        } else {
+            t!("history.fanout_failed", failed = failed, count = result.targets.len())
+        },
+        target_id: String::new(),
+        snapshot_id: None,
+        installed_extensions: Vec::new(),
+        undone: false,
+        options: Some(options.clone()),
+        files: result.targets.iter().flat_map(|t| t.result.files.clone()).collect(),
+        failed_extensions: result.targets.iter().flat_map(|t| t.result.failed_extensions.clone()).collect(),
+        duration_ms: result.duration_ms,
+        log: result.targets.iter().flat_map(|t| t.result.log.clone()).collect(),
+        targets: result
+            .targets
+            .iter()
+            .map(|t| TargetRecord {
+                target_id: t.target_id.clone(),
+                target_name: t.target_name.clone(),
+                success: t.result.success,
+                snapshot_id: t.result.snapshot_id.clone(),
+                installed_extensions: t.result.installed_extensions.clone(),
+            })
+            .collect(),
+        conflicts: Vec::new(),
+        source_installed_extensions: Vec::new(),
+        group_id: None,
+        group_name: None,
+        trigger: SyncTrigger::Manual,
+        job_id: None,
+    }
+}
+
+fn join_names<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names.collect::<Vec<_>>().join(", ")
+}
+
+fn snapshot_target_id(snapshot: &backup::Snapshot) -> String {
+    snapshot
+        .files
+        .first()
+        .and_then(|f| ide::find_ide_for_path(&f.original_path))
+        .map(|i| i.id)
+        .unwrap_or_default()
+}
+
+/// Records a restore in history so it shows up next to syncs and can be undone
+pub fn record_restore(snapshot: &backup::Snapshot, source_name: String, details: String, log: Vec<String>) {
+    append_history(restore_record(snapshot, source_name, details, log));
+}
+
+fn restore_record(snapshot: &backup::Snapshot, source_name: String, details: String, log: Vec<String>) -> SyncRecord {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let target_id = snapshot_target_id(snapshot);
+
+    SyncRecord {
+        id: snapshot.record_id.clone().unwrap_or_else(new_record_id),
+        timestamp,
+        source_name,
+        source_id: String::new(),
+        target_name: snapshot.target_ide.clone(),
+        status: "Success".to_string(),
+        details,
+        target_id,
+        snapshot_id: Some(snapshot.id.clone()),
+        installed_extensions: Vec::new(),
+        undone: false,
+        options: None,
+        files: backup::snapshot_outcomes(snapshot),
+        failed_extensions: Vec::new(),
+        duration_ms: 0,
+        log,
+        targets: Vec::new(),
+        conflicts: Vec::new(),
+        source_installed_extensions: Vec::new(),
+        group_id: None,
+        group_name: None,
+        trigger: SyncTrigger::Manual,
+        job_id: None,
+    }
+}
+
+/// Records an undo as one entry. `snapshots` are the snapshots the undo
+/// created, one per restored target; `success` is false when some target
+/// could not be restored, and the entry then shows as failed.
+pub fn record_undo(snapshots: &[backup::Snapshot], source_name: String, details: String, log: Vec<String>, success: bool) {
+    let Some(first) = snapshots.first() else {
+        return;
+    };
+    let mut record = if snapshots.len() == 1 {
+        restore_record(first, source_name, details, log)
+    } else {
+        group_restore_record(snapshots, source_name, details, log)
+    };
+    if !success {
+        record.status = "Failed".to_string();
+    }
+    append_history(record);
+}
+
+/// A restore that touched several targets (undoing a grouped sync) as one grouped entry
+fn group_restore_record(snapshots: &[backup::Snapshot], source_name: String, details: String, log: Vec<String>) -> SyncRecord {
+    let first = &snapshots[0];
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    SyncRecord {
+        id: first.record_id.clone().unwrap_or_else(new_record_id),
+        timestamp,
+        source_name,
+        source_id: String::new(),
+        target_name: join_names(snapshots.iter().map(|s| s.target_ide.as_str())),
+        status: "Success".to_string(),
+        details,
+        target_id: String::new(),
+        snapshot_id: None,
+        installed_extensions: Vec::new(),
+        undone: false,
+        options: None,
+        files: snapshots.iter().flat_map(backup::snapshot_outcomes).collect(),
+        failed_extensions: Vec::new(),
+        duration_ms: 0,
+        log,
+        targets: snapshots
+            .iter()
+            .map(|s| TargetRecord {
+                target_id: snapshot_target_id(s),
+                target_name: s.target_ide.clone(),
+                success: true,
+                snapshot_id: Some(s.id.clone()),
+                installed_extensions: Vec::new(),
+            })
+            .collect(),
+        conflicts: Vec::new(),
+        source_installed_extensions: Vec::new(),
+        group_id: None,
+        group_name: None,
+        trigger: SyncTrigger::Manual,
+        job_id: None,
+    }
+}
+
+/// 列表中只需要概要，完整日志通过 `find_record` 单独获取
+pub fn summarize(mut record: SyncRecord) -> SyncRecord {
+    record.log = Vec::new();
+    record
+}
+
+pub fn find_record(record_id: &str) -> Option<SyncRecord> {
+    read_history().into_iter().find(|r| r.id == record_id)
+}
+
+/// 撤销一条记录。查找记录、执行 `undo` 和写入撤销标记都在历史锁内完成，
+/// 同一条记录不会被并发撤销两次。只要恢复了任何文件（即使部分目标失败）就写入撤销标记，
+/// 因为已恢复的文件无法再按原快照撤销。`undo` 不能写入历史。
+pub fn undo_record(record_id: &str, undo: impl FnOnce(&SyncRecord) -> Result<SyncResult, AppError>) -> Result<SyncResult, AppError> {
+    let _lock = lock_history()?;
+    let record = read_history()
+        .into_iter()
+        .find(|r| r.id == record_id)
+        .ok_or_else(|| AppError::RecordNotFound { id: record_id.to_string() })?;
+
+    let result = undo(&record)?;
+    if result.success || !result.files.is_empty() {
+        let entry = LogEntry::Undone { id: record_id.to_string() };
+        if let Err(e) = append_entry_locked(&entry) {
+            eprintln!("{}", t!("warn.history_failed", error = e));
+        }
+    }
+    Ok(result)
+}
+
+/// 历史查询条件，所有字段均可省略
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HistoryQuery {
+    /// 源或目标 IDE 的名称，或目标 IDE 的 id（分组记录匹配其中任一目标）
+    pub ide: Option<String>,
+    /// "Success" 或 "Failed"
+    pub status: Option<String>,
+    /// 同步组的 ID 或名称，只返回该组触发的同步
+    pub group: Option<String>,
+    /// 只返回手动、自动或定时触发的同步
+    pub trigger: Option<SyncTrigger>,
+    /// 定时任务 ID，只返回该任务触发的同步
+    pub job: Option<String>,
+    /// 时间范围（Unix 秒，含两端）
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub offset: usize,
+    /// 每页数量，省略时返回全部
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    fn matches(&self, record: &SyncRecord) -> bool {
+        self.ide.as_ref().is_none_or(|ide| {
+            &record.source_name == ide
+                || &record.target_name == ide
+                || &record.target_id == ide
+                || record.targets.iter().any(|t| &t.target_name == ide || &t.target_id == ide)
+        }) && self.status.as_ref().is_none_or(|s| &record.status == s)
+            && self.group.as_ref().is_none_or(|g| {
+                record.group_id.as_ref() == Some(g) || record.group_name.as_ref() == Some(g)
+            })
+            && self.trigger.is_none_or(|t| record.trigger == t)
+            && self.job.as_ref().is_none_or(|j| record.job_id.as_ref() == Some(j))
+            && self.from.is_none_or(|from| record.timestamp >= from)
+            && self.to.is_none_or(|to| record.timestamp <= to)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub records: Vec<SyncRecord>,
+    /// 满足条件的记录总数（分页前）
+    pub total: usize,
+}
+
+/// 按条件查询历史，最新的在前。返回的记录不含完整日志。
+pub fn query_history(query: &HistoryQuery) -> HistoryPage {
+    let matched: Vec<SyncRecord> = read_history()
+        .into_iter()
+        .filter(|r| query.matches(r))
+        .collect();
+    let total = matched.len();
+    let records = matched
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .map(summarize)
+        .collect();
+
+    HistoryPage { records, total }
+}
+
+/// 按保留策略压缩日志：合并撤销标记，删除过期记录。返回删除的记录数。
+///
+/// 旧版 history.json 不受影响。
+pub fn compact_history() -> Result<usize, AppError> {
+    let policy = retention::load_config().history;
+    let _lock = lock_history()?;
+    compact_locked(&policy)
+}
+
+/// 两次压缩之间最多追加的记录数：`keep_last` 的十分之一，限制在 10 到 100 之间
+fn compaction_slack(policy: &retention::HistoryRetention) -> usize {
+    (policy.keep_last / 10).clamp(10, 100)
+}
+
+/// 调用方须持有 [`lock_history`]
+fn compact_locked(policy: &retention::HistoryRetention) -> Result<usize, AppError> {
+    let mut records = Vec::new();
+    let undone = fold_log(read_log(), &mut records);
+    let before = records.len();
+
+    if policy.keep_days > 0 {
+        let cutoff = Local::now().timestamp() - i64::from(policy.keep_days) * 24 * 60 * 60;
+        records.retain(|r| r.timestamp >= cutoff);
+    }
+    if policy.keep_last > 0 && records.len() > policy.keep_last {
+        records.drain(..records.len() - policy.keep_last);
+    }
+    let removed = before - records.len();
+
+    // 撤销标记已合并进记录；只保留指向旧版记录的标记
+    let legacy: HashSet<String> = read_legacy_history().into_iter().map(|r| r.id).collect();
+    let mut entries: Vec<LogEntry> = undone
+        .into_iter()
+        .filter(|id| legacy.contains(id))
+        .map(|id| LogEntry::Undone { id })
+        .collect();
+    entries.extend(records.into_iter().map(|r| LogEntry::Record(Box::new(r))));
+
+    let mut content = String::new();
+    for entry in &entries {
+        let line = serde_json::to_string(entry).map_err(AppError::serialization)?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    // 先写临时文件再替换，避免中途失败丢失历史
+    let path = get_log_file_path();
+    let tmp_path = path.with_extension("jsonl.tmp");
+    fs::write(&tmp_path, content).map_err(|e| AppError::io(&tmp_path, e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| AppError::io(&path, e))?;
+    let _ = fs::remove_file(get_count_file_path());
+
+    Ok(removed)
+}
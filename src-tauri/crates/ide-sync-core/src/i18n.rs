@@ -0,0 +1,203 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// 后端输出（同步日志、错误、历史详情）使用的语言
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Locale {
+    /// 与加入多语言之前的输出一致
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct LocaleFile {
+    locale: Locale,
+}
+
+static CURRENT: RwLock<Option<Locale>> = RwLock::new(None);
+
+fn get_locale_file_path() -> PathBuf {
+    crate::data_dir().join("locale.json")
+}
+
+/// 当前语言，首次调用时从 locale.json 读取
+pub fn current_locale() -> Locale {
+    if let Some(locale) = *CURRENT.read().unwrap_or_else(|e| e.into_inner()) {
+        return locale;
+    }
+    let locale = fs::read_to_string(get_locale_file_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<LocaleFile>(&content).ok())
+        .map(|file| file.locale)
+        .unwrap_or_default();
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(locale);
+    locale
+}
+
+pub fn set_locale(locale: Locale) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(&LocaleFile { locale }).map_err(AppError::serialization)?;
+    let path = get_locale_file_path();
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))?;
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(locale);
+    Ok(())
+}
+
+/// 按当前语言取出消息模板并替换 `{name}` 参数。缺少译文时回退到英文，再回退到 key 本身。
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    let template = MESSAGES
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, en, zh)| match current_locale() {
+            Locale::En => *en,
+            Locale::ZhCn => *zh,
+        })
+        .unwrap_or(key);
+
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+/// `t!("sync.start", source = a, target = b)` 等价于 `translate("sync.start", &[("source", a), ...])`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// (key, English, 简体中文)
+static MESSAGES: &[(&str, &str, &str)] = &[
+    // 同步日志
+    ("sync.start", "Starting sync from {source} to {target}...", "开始从 {source} 同步到 {target}..."),
+    ("sync.strategy", "Strategy: {strategy}", "合并策略: {strategy}"),
+    ("sync.section.settings", "--- Syncing Settings ---", "--- 同步配置 ---"),
+    ("sync.section.keybindings", "--- Syncing Keybindings ---", "--- 同步快捷键 ---"),
+    ("sync.section.snippets", "--- Syncing Snippets ---", "--- 同步代码片段 ---"),
+    ("sync.section.extensions", "--- Syncing Extensions (CLI Method) ---", "--- 同步扩展（命令行） ---"),
+    ("sync.error.settings", "Error syncing settings: {error}", "同步配置出错: {error}"),
+    ("sync.error.keybindings", "Error syncing keybindings: {error}", "同步快捷键出错: {error}"),
+    ("sync.error.snippet", "Error syncing snippet {path}: {error}", "同步代码片段 {path} 出错: {error}"),
+    ("sync.error.extensions", "Error syncing extensions: {error}", "同步扩展出错: {error}"),
+    ("sync.keybindings_missing", "Source keybindings.json not found, skipping.", "源 IDE 没有 keybindings.json，跳过。"),
+    ("sync.no_snippets", "No snippets found in source, skipping.", "源 IDE 没有代码片段，跳过。"),
+    ("sync.extensions_dry_run", "[Dry Run] Would run extension install commands.", "[空跑] 将执行扩展安装命令。"),
+    ("sync.extensions_done", "Extensions sync completed.", "扩展同步完成。"),
+    ("sync.bidi_first", "First bidirectional sync between these IDEs: settings that differ on both sides are reported as conflicts.", "这两个 IDE 之间首次双向同步：两边不同的设置项将作为冲突列出。"),
+    ("sync.conflict", "Conflict in {file}: {key} changed on both sides, each side keeps its own value", "冲突: {file} 中的 {key} 两边都已修改，各自保留原值"),
+    ("sync.conflicts_summary", "{count} conflict(s) need to be resolved manually", "共 {count} 处冲突需要手动处理"),
+    ("sync.state_failed", "Warning: Failed to save bidirectional sync state: {error}", "警告: 保存双向同步状态失败: {error}"),
+    ("sync.snapshot_recorded", "Snapshot {id} recorded.", "已记录快照 {id}。"),
+    ("sync.snapshot_failed", "Warning: Failed to record snapshot: {error}", "警告: 记录快照失败: {error}"),
+    ("sync.retention_removed", "Retention policy removed {count} old backup(s) of {path}", "保留策略清理了 {path} 的 {count} 个旧备份"),
+    ("sync.retention_failed", "Warning: Backup cleanup failed for {path}: {error}", "警告: 清理 {path} 的备份失败: {error}"),
+    ("file.dry_run", "[Dry Run] Would write to {path} (Strategy: {strategy})", "[空跑] 将写入 {path}（策略: {strategy}）"),
+    ("file.synced", "Successfully synced {path} (Strategy: {strategy}, auto-backup created)", "已同步 {path}（策略: {strategy}，已自动备份）"),
+    ("file.unchanged", "{path} is already up to date", "{path} 已是最新"),
+    ("file.source_keybindings_missing", "Source keybindings.json not found", "源 IDE 没有 keybindings.json"),
+    ("file.restored", "Restored", "已恢复"),
+    ("file.deleted", "Deleted", "已删除"),
+    // 扩展
+    ("ext.found", "Found {count} extensions in {ide}", "在 {ide} 中找到 {count} 个扩展"),
+    ("ext.installing", "Installing {ext} on {ide}...", "正在为 {ide} 安装 {ext}..."),
+    ("ext.install_failed", "Failed to install {ext}: {error}", "安装 {ext} 失败: {error}"),
+    ("ext.install_exec_failed", "Failed to execute install command: {error}", "无法执行安装命令: {error}"),
+    ("ext.manifest_written", "Wrote {count} extensions to the manifest {path}", "已将 {count} 个扩展写入清单 {path}"),
+    ("ext.uninstalling", "Uninstalling {ext} from {ide}...", "正在从 {ide} 卸载 {ext}..."),
+    ("ext.uninstall_failed", "Failed to uninstall {ext}: {error}", "卸载 {ext} 失败: {error}"),
+    ("ext.uninstall_exec_failed", "Failed to execute uninstall command: {error}", "无法执行卸载命令: {error}"),
+    // 撤销 / 恢复
+    ("undo.start", "Undoing sync {id} ({source} -> {target})...", "正在撤销同步 {id}（{source} -> {target}）..."),
+    ("undo.no_files", "No files were modified by this sync.", "该同步没有修改任何文件。"),
+    ("undo.target_failed", "Failed to restore {target}: {error}", "恢复 {target} 失败：{error}"),
+    ("undo.target_missing", "Target IDE not found, installed extensions were left in place.", "未找到目标 IDE，已安装的扩展未卸载。"),
+    ("restore.backup_done", "Restored to: {path}", "已恢复到: {path}"),
+    ("restore.snapshot_done", "Restored snapshot {id} ({count} files)", "已恢复快照 {id}，共 {count} 个文件"),
+    ("backup.deleted", "Backup deleted", "备份已删除"),
+    ("snapshot.deleted", "Snapshot deleted", "快照已删除"),
+    // 历史详情
+    ("history.sync_success", "Sync succeeded", "同步成功"),
+    ("history.sync_failed", "Sync failed ({count} log lines)", "同步失败，共 {count} 条日志"),
+    ("history.fanout_success", "Synced to {count} targets", "已同步到 {count} 个目标"),
+    ("history.fanout_failed", "{failed} of {count} targets failed", "{count} 个目标中 {failed} 个失败"),
+    ("history.restore_backup", "Restored backup {path}", "恢复备份 {path}"),
+    ("history.restore_snapshot", "Restored snapshot {id}", "恢复快照 {id}"),
+    ("history.undo", "Undid sync {id}", "撤销同步 {id}"),
+    // 定时任务
+    ("schedule.interval", "every {minutes} min", "每 {minutes} 分钟"),
+    // 警告（输出到标准错误）
+    ("warn.create_dir_failed", "Warning: Failed to create {path}: {error}", "警告：无法创建 {path}：{error}"),
+    ("warn.history_failed", "Warning: Failed to record history: {error}", "警告：写入历史失败：{error}"),
+    ("warn.history_compaction_failed", "Warning: History compaction failed: {error}", "警告：压缩历史失败：{error}"),
+    ("warn.legacy_migration_failed", "Warning: Legacy backup migration failed: {error}", "警告：迁移旧版备份失败：{error}"),
+    ("warn.legacy_migration_failed_for", "Warning: Legacy backup migration failed for {ide}: {error}", "警告：迁移 {ide} 的旧版备份失败：{error}"),
+    ("warn.config_migration_failed", "Warning: Failed to save migrated config: {error}", "警告：保存升级后的配置失败：{error}"),
+    ("warn.job_state_failed", "Warning: Failed to save scheduled job state: {error}", "警告：保存定时任务状态失败：{error}"),
+    ("warn.watcher_error", "Warning: File watcher error: {error}", "警告：文件监听出错：{error}"),
+    ("warn.watch_failed", "Warning: Failed to watch {path}: {error}", "警告：无法监听 {path}：{error}"),
+    ("warn.auto_sync_unavailable", "Warning: Auto sync is unavailable: {error}", "警告：自动同步不可用：{error}"),
+    // 命令行
+    ("cli.error", "Error: {error}", "错误：{error}"),
+    ("cli.record", "record: {id}", "记录：{id}"),
+    ("cli.success", "Success", "成功"),
+    ("cli.failed", "Failed", "失败"),
+    ("cli.run_done", "{name}: {status}  record: {id}", "{name}：{status}  记录：{id}"),
+    ("cli.undone", "(undone)", "（已撤销）"),
+    ("cli.trigger_auto", "(auto)", "（自动）"),
+    ("cli.trigger_scheduled", "(scheduled)", "（定时）"),
+    ("cli.records_page", "{shown} of {total} records", "显示 {shown} 条，共 {total} 条记录"),
+    ("cli.backups_page", "{shown} of {total} backups", "显示 {shown} 个，共 {total} 个备份"),
+    ("cli.backups_removed", "removed {count} backup(s)", "已删除 {count} 个备份"),
+    ("cli.group_created", "created group {name} ({id})", "已创建同步组 {name}（{id}）"),
+    ("cli.group_deleted", "deleted group {name}", "已删除同步组 {name}"),
+    ("cli.job_created", "created job {name} ({id})", "已创建定时任务 {name}（{id}）"),
+    ("cli.job_deleted", "deleted job {name}", "已删除定时任务 {name}"),
+    ("cli.job_status", "next: {next}  last: {last}", "下次：{next}  上次：{last}"),
+    ("cli.job_disabled", "disabled", "已停用"),
+    ("cli.preset_created", "created preset {name}", "已创建预设 {name}"),
+    ("cli.preset_deleted", "deleted preset {name}", "已删除预设 {name}"),
+    // 错误（key 为 "error." + AppError 的 code）
+    ("error.ide_not_found", "IDE not found: {id}", "未找到 IDE: {id}"),
+    ("error.file_not_found", "File not found: {path}", "文件不存在: {path}"),
+    ("error.io_error", "Failed to read or write {path}: {detail}", "读写文件失败: {path}: {detail}"),
+    ("error.invalid_path", "Invalid file path: {path}", "无效的文件路径: {path}"),
+    ("error.invalid_jsonc", "Not valid JSONC: {path}", "不是有效的 JSONC: {path}"),
+    ("error.cli_not_found", "Failed to run '{executable}'. CLI not found or not installed.", "无法运行 '{executable}'，命令行工具未安装或不在 PATH 中"),
+    ("error.cli_failed", "'{executable}' failed: {detail}", "'{executable}' 执行失败: {detail}"),
+    ("error.lock_failed", "Failed to lock {path}: {detail}", "锁定失败: {path}: {detail}"),
+    ("error.backup_not_found", "Backup not found: {path}", "备份不存在: {path}"),
+    ("error.backup_corrupt", "Backup failed verification and may be corrupt: {path}", "备份内容校验失败，文件可能已损坏: {path}"),
+    ("error.snapshot_not_found", "Snapshot not found: {id}", "快照不存在: {id}"),
+    ("error.snapshot_backup_missing", "A backup in this snapshot has been cleaned up: {path}", "快照中的备份已被清理: {path}"),
+    ("error.modified_since_sync", "File was modified after the sync, refusing to undo: {path}", "文件在同步后已被修改，拒绝撤销: {path}"),
+    ("error.missing_checksum", "Snapshot has no post-sync checksum, cannot confirm file state: {path}", "快照缺少同步后的校验信息，无法确认文件状态: {path}"),
+    ("error.record_not_found", "Sync record not found: {id}", "同步记录不存在: {id}"),
+    ("error.already_undone", "Sync already undone: {id}", "该同步已撤销: {id}"),
+    ("error.invalid_archive", "Not a valid config archive: {path}", "不是有效的配置归档: {path}"),
+    ("error.unsupported_archive_version", "Archive format version {version} is too new, please upgrade the app", "归档格式版本 {version} 过新，请升级应用后再导入"),
+    ("error.invalid_archive_entry", "Invalid snippet file name in archive: {name}", "归档中的代码片段文件名无效: {name}"),
+    ("error.unsupported_strategy", "The {strategy} strategy is not supported here", "此操作不支持 {strategy} 策略"),
+    ("error.group_not_found", "Sync group not found: {id}", "同步组不存在: {id}"),
+    ("error.invalid_group_name", "Sync group name is empty or already in use: {name}", "同步组名称为空或已被使用: {name}"),
+    ("error.invalid_group_targets", "Sync group {name} needs at least one target other than its source", "同步组 {name} 至少需要一个与源不同的目标"),
+    ("error.job_not_found", "Scheduled job not found: {id}", "定时任务不存在: {id}"),
+    ("error.invalid_job_name", "Scheduled job name is empty or already in use: {name}", "定时任务名称为空或已被使用: {name}"),
+    ("error.invalid_job_targets", "Scheduled job {name} needs at least one target other than its source, and exactly one with the bidirectional strategy", "定时任务 {name} 至少需要一个与源不同的目标，双向同步只能有一个目标"),
+    ("error.invalid_schedule", "Invalid schedule: {schedule}", "无效的定时设置: {schedule}"),
+    ("error.preset_not_found", "Sync preset not found: {name}", "同步预设不存在: {name}"),
+    ("error.invalid_preset_name", "Sync preset name is empty or already in use: {name}", "同步预设名称为空或已被使用: {name}"),
+    ("error.invalid_preset_targets", "Sync preset {name} needs exactly one target other than its source", "同步预设 {name} 需要一个与源不同的目标"),
+    ("error.serialization_error", "Serialization failed: {detail}", "序列化失败: {detail}"),
+    ("error.internal_error", "Internal error: {detail}", "内部错误: {detail}"),
+];
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::models::{IdeProfile, IdeType};
 
 pub fn get_default_ides() -> Vec<IdeProfile> {
@@ -210,12 +210,75 @@ pub fn get_default_ides() -> Vec<IdeProfile> {
         });
     }
 
+    // 15. 应用自己的标准配置，始终可用
+    profiles.push(get_profile_ide());
+
     profiles
 }
 
+/// Directory of the app-owned canonical profile: `settings.json`,
+/// `keybindings.json`, `snippets/` and the `extensions.json` manifest.
+pub fn get_profile_dir() -> PathBuf {
+    crate::data_dir().join("profile")
+}
+
+/// The canonical profile as an IDE, so it can be synced from and to like an
+/// editor. Its files may not exist until something is synced into it.
+pub fn get_profile_ide() -> IdeProfile {
+    let dir = get_profile_dir();
+    IdeProfile {
+        id: "profile".to_string(),
+        name: "Golden Profile".to_string(),
+        ide_type: IdeType::Profile,
+        config_path: dir.join("settings.json").to_string_lossy().to_string(),
+        extensions_path: dir.join("extensions.json").to_string_lossy().to_string(),
+        executable: String::new(),
+        is_detected: true,
+    }
+}
+
+/// Finds the detected IDE a config file belongs to: anything under its User
+/// directory (settings, keybindings, snippets), or its extension manifest.
+pub fn find_ide_for_path(file_path: &str) -> Option<IdeProfile> {
+    let path = Path::new(file_path);
+    get_default_ides()
+        .into_iter()
+        .filter_map(|ide| {
+            let user_dir = Path::new(&ide.config_path).parent()?.to_path_buf();
+            let owns = path.starts_with(&user_dir)
+                || (ide.uses_extension_manifest() && path == Path::new(&ide.extensions_path));
+            // Prefer the deepest directory if one IDE lives inside another
+            owns.then(|| (user_dir.components().count(), ide))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, ide)| ide)
+}
+
 pub fn get_keybindings_path(config_path: &str) -> PathBuf {
     let mut path = PathBuf::from(config_path);
     path.pop(); // Remove settings.json
     path.push("keybindings.json");
     path
 }
+
+pub fn get_snippets_dir(config_path: &str) -> PathBuf {
+    let mut path = PathBuf::from(config_path);
+    path.pop(); // Remove settings.json
+    path.push("snippets");
+    path
+}
+
+/// Snippet files (`*.json` and `*.code-snippets`) directly inside the snippets dir.
+pub fn list_snippet_files(config_path: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(get_snippets_dir(config_path)) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("json") | Some("code-snippets")))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
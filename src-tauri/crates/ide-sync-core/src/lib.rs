@@ -0,0 +1,49 @@
+//! IDE Sync Pro 的同步引擎，不依赖 Tauri 和 webview
+//!
+//! - [`ide`]：检测本机安装的 IDE 及其配置路径
+//! - [`sync`]：在两个 IDE 之间同步配置、快捷键、代码片段和扩展
+//! - [`bidirectional`]：基于上次同步结果的三方合并
+//! - [`groups`]：保存的同步组，一次同步一个源到多个目标
+//! - [`watcher`]：监听源 IDE 的配置文件，变化后自动运行同步组
+//! - [`scheduler`]：按间隔或 cron 表达式运行的定时任务
+//! - [`config`]：应用设置和命名的同步预设
+//! - [`backup`]：集中备份存储、快照、恢复与校验
+//! - [`history`]：同步历史日志
+//! - [`retention`]：备份与历史的保留策略
+//! - [`diff`] / [`archive`]：备份对比、配置归档的导入导出
+//!
+//! 所有失败都以 [`AppError`] 返回。数据保存在 `~/.ide-sync-pro/`，桌面版与命令行共用。
+
+pub mod error;
+pub mod i18n;
+pub mod models;
+pub mod ide;
+pub mod sync;
+pub mod bidirectional;
+pub mod groups;
+pub mod watcher;
+pub mod scheduler;
+pub mod config;
+pub mod history;
+pub mod backup;
+pub mod retention;
+pub mod diff;
+pub mod archive;
+
+pub use error::AppError;
+
+use std::fs;
+use std::path::PathBuf;
+
+/// 数据目录 `~/.ide-sync-pro/`，不存在时创建
+pub fn data_dir() -> PathBuf {
+    ensure_dir(dirs::home_dir().expect("Could not determine home directory").join(".ide-sync-pro"))
+}
+
+/// 创建目录（已存在时不做任何事）并返回它。创建失败只打印警告，之后读写其中的文件时会返回具体的错误。
+pub(crate) fn ensure_dir(path: PathBuf) -> PathBuf {
+    if let Err(e) = fs::create_dir_all(&path) {
+        eprintln!("{}", t!("warn.create_dir_failed", path = path.display(), error = e));
+    }
+    path
+}
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum IdeType {
+    VSCode,
+    VSCodeInsiders,
+    VSCodium,
+    Cursor,
+    CursorNightly,
+    Windsurf,
+    Antigravity,
+    Kiro,
+    Lingma,
+    Trae,
+    Positron,
+    Codeium,
+    CodeOSS,
+    Archive, // 从导出的归档文件解包出的配置
+    Profile, // 应用自己保存的标准配置，不属于任何编辑器
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdeProfile {
+    pub id: String,
+    pub name: String,
+    pub ide_type: IdeType,
+    pub config_path: String,      // Path to settings.json
+    pub extensions_path: String,  // Path to extensions dir
+    pub executable: String,       // Command to launch/install extensions (e.g. 'code', 'cursor')
+    pub is_detected: bool,
+}
+
+impl IdeProfile {
+    /// 扩展列表保存在 extensions_path 指向的 JSON 清单中，而不是通过编辑器 CLI 获取
+    pub fn uses_extension_manifest(&self) -> bool {
+        matches!(self.ide_type, IdeType::Archive | IdeType::Profile)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncOptions {
+    pub sync_settings: bool,
+    pub sync_extensions: bool,
+    pub sync_keybindings: bool,
+    /// 归档中带有代码片段，导入归档走 `perform_sync`，因此片段同步是通用的同步选项
+    #[serde(default)]
+    pub sync_snippets: bool,
+    pub dry_run: bool,
+    pub strategy: SyncStrategy,
+}
+
+/// 同步时排除的内容，随同步组保存
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SyncFilters {
+    /// 不同步的设置项；以 `*` 结尾时按前缀匹配，例如 `workbench.*`
+    pub exclude_settings: Vec<String>,
+    /// 不同步的扩展 ID，不区分大小写
+    pub exclude_extensions: Vec<String>,
+    /// 不同步的代码片段文件名，例如 `python.json`
+    pub exclude_snippets: Vec<String>,
+}
+
+impl SyncFilters {
+    pub fn excludes_setting(&self, key: &str) -> bool {
+        self.exclude_settings.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        })
+    }
+
+    pub fn excludes_extension(&self, id: &str) -> bool {
+        self.exclude_extensions.iter().any(|e| e.eq_ignore_ascii_case(id))
+    }
+
+    pub fn excludes_snippet(&self, file_name: &str) -> bool {
+        self.exclude_snippets.iter().any(|s| s == file_name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum SyncStrategy {
+    Overwrite,
+    Merge,
+    Bidirectional, // 三方合并，两边的修改互相同步，冲突保留各自的值
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum FileStatus {
+    Synced,
+    DryRun,  // 空跑，未写入
+    Skipped, // 源文件不存在
+    Deleted, // 恢复到"不存在"的状态时删除了文件
+    Unchanged, // 双向同步时内容已一致，未写入
+    Failed,
+}
+
+/// 双向同步中两边自上次同步以来都修改了的设置项，两边各自保留原值
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncConflict {
+    pub file_type: String,
+    pub file: String, // 源 IDE 中的文件名，例如 settings.json
+    pub key: String,  // 设置项路径，嵌套的键用 / 分隔
+    pub source_value: Option<serde_json::Value>, // None 表示该侧已删除
+    pub target_value: Option<serde_json::Value>,
+}
+
+/// 单个文件在一次同步中的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileOutcome {
+    pub file_type: String,
+    pub path: String, // 目标文件路径
+    pub status: FileStatus,
+    pub backup_path: Option<String>, // 写入前创建的备份
+    pub message: String,
+    #[serde(default)]
+    pub error_code: Option<String>, // 失败时的错误码，与 AppError 的 code 一致
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncResult {
+    #[serde(default)]
+    pub record_id: String, // 对应的历史记录 ID
+    pub success: bool,
+    pub log: Vec<String>,
+    #[serde(default)]
+    pub snapshot_id: Option<String>, // 本次同步创建的快照，dry run 时为空
+    #[serde(default)]
+    pub installed_extensions: Vec<String>, // 本次同步新安装到目标的扩展
+    #[serde(default)]
+    pub failed_extensions: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<FileOutcome>,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>, // 双向同步中未能自动合并的设置项
+    #[serde(default)]
+    pub source_installed_extensions: Vec<String>, // 双向同步时新安装到源的扩展
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncRecord {
+    pub id: String, // uuid; legacy records use the Unix timestamp
+    pub timestamp: i64,
+    pub source_name: String,
+    /// 源 IDE 的 id；旧版记录和恢复记录为空
+    #[serde(default)]
+    pub source_id: String,
+    pub target_name: String,
+    pub status: String, // "Success" or "Failed"
+    pub details: String,
+    #[serde(default)]
+    pub target_id: String,
+    #[serde(default)]
+    pub snapshot_id: Option<String>,
+    #[serde(default)]
+    pub installed_extensions: Vec<String>,
+    #[serde(default)]
+    pub undone: bool,
+    #[serde(default)]
+    pub options: Option<SyncOptions>, // 恢复/撤销记录为空
+    #[serde(default)]
+    pub files: Vec<FileOutcome>,
+    #[serde(default)]
+    pub failed_extensions: Vec<String>,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub log: Vec<String>, // 完整日志，get_history 列表中不返回
+    #[serde(default)]
+    pub targets: Vec<TargetRecord>, // 一对多同步的各个目标；单目标记录为空
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+    #[serde(default)]
+    pub source_installed_extensions: Vec<String>,
+    #[serde(default)]
+    pub group_id: Option<String>, // 由同步组触发时的组 ID
+    #[serde(default)]
+    pub group_name: Option<String>,
+    #[serde(default)]
+    pub trigger: SyncTrigger,
+    #[serde(default)]
+    pub job_id: Option<String>, // 由定时任务触发时的任务 ID
+}
+
+/// 同步由什么触发
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum SyncTrigger {
+    #[default]
+    Manual,
+    Auto, // 监听到源 IDE 的配置文件变化后自动运行
+    Scheduled, // 定时任务
+}
+
+/// 分组历史记录中的单个目标，撤销时逐个恢复
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TargetRecord {
+    pub target_id: String,
+    pub target_name: String,
+    pub success: bool,
+    pub snapshot_id: Option<String>,
+    pub installed_extensions: Vec<String>,
+}
+
+/// 一对多同步中单个目标的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TargetResult {
+    pub target_id: String,
+    pub target_name: String,
+    pub result: SyncResult,
+}
+
+/// 一对多同步的结果，所有目标共用一条历史记录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FanoutResult {
+    pub record_id: String,
+    pub success: bool, // 所有目标都成功
+    pub targets: Vec<TargetResult>,
+    pub duration_ms: u64,
+}
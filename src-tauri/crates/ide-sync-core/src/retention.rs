@@ -0,0 +1,275 @@
+use crate::backup::BackupInfo;
+use crate::error::AppError;
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// 备份保留策略。满足任意一条规则的备份都会被保留，最新的备份始终保留。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// 保留最新的 N 个备份
+    pub keep_last: usize,
+    /// 保留最近 D 天内的全部备份（0 表示不启用）
+    pub keep_days: u32,
+    /// 保留最近 N 天中每天最新的一个备份
+    pub keep_daily: usize,
+    /// 保留最近 N 周中每周最新的一个备份
+    pub keep_weekly: usize,
+    /// 保留最近 N 个月中每月最新的一个备份
+    pub keep_monthly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        // 与旧版固定保留 10 个的行为一致
+        Self {
+            keep_last: 10,
+            keep_days: 0,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: 0,
+        }
+    }
+}
+
+/// 同步历史的保留策略，两条规则同时生效
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HistoryRetention {
+    /// 最多保留的记录数（0 表示不限）
+    pub keep_last: usize,
+    /// 删除早于 D 天的记录（0 表示不启用）
+    pub keep_days: u32,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            keep_last: 1000,
+            keep_days: 0,
+        }
+    }
+}
+
+/// 全局策略，以及按 IDE id 覆盖的策略
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub global: RetentionPolicy,
+    #[serde(default)]
+    pub per_ide: HashMap<String, RetentionPolicy>,
+    #[serde(default)]
+    pub history: HistoryRetention,
+}
+
+impl RetentionConfig {
+    pub fn policy_for(&self, ide_id: Option<&str>) -> &RetentionPolicy {
+        ide_id
+            .and_then(|id| self.per_ide.get(id))
+            .unwrap_or(&self.global)
+    }
+}
+
+pub fn get_retention_file_path() -> PathBuf {
+    crate::data_dir().join("retention.json")
+}
+
+pub fn load_config() -> RetentionConfig {
+    match fs::read_to_string(get_retention_file_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => RetentionConfig::default(),
+    }
+}
+
+pub fn save_config(config: &RetentionConfig) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(config).map_err(AppError::serialization)?;
+    let path = get_retention_file_path();
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))
+}
+
+/// 按策略挑选需要删除的备份。`backups` 需按时间倒序排列。
+pub fn select_expired(backups: &[BackupInfo], policy: &RetentionPolicy, now: DateTime<Local>) -> Vec<BackupInfo> {
+    let mut keep: HashSet<usize> = HashSet::new();
+
+    // 最新的备份始终保留，保证刚创建的备份不会被立即清理
+    keep.extend(0..policy.keep_last.max(1).min(backups.len()));
+
+    let local_times: Vec<DateTime<Local>> = backups
+        .iter()
+        .map(|b| {
+            DateTime::from_timestamp(b.metadata.timestamp, 0)
+                .unwrap_or_default()
+                .into()
+        })
+        .collect();
+
+    if policy.keep_days > 0 {
+        let cutoff = now - chrono::Duration::days(policy.keep_days as i64);
+        keep.extend(local_times.iter().enumerate().filter(|(_, t)| **t >= cutoff).map(|(i, _)| i));
+    }
+
+    // 祖父-父-子：每个周期保留最新的一个，最多保留 N 个周期
+    keep_per_period(&local_times, policy.keep_daily, &mut keep, |t| (t.year(), t.ordinal()));
+    keep_per_period(&local_times, policy.keep_weekly, &mut keep, |t| {
+        let week = t.iso_week();
+        (week.year(), week.week())
+    });
+    keep_per_period(&local_times, policy.keep_monthly, &mut keep, |t| (t.year(), t.month()));
+
+    backups
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !keep.contains(i))
+        .map(|(_, b)| b.clone())
+        .collect()
+}
+
+fn keep_per_period<F>(times: &[DateTime<Local>], periods: usize, keep: &mut HashSet<usize>, period_of: F)
+where
+    F: Fn(&DateTime<Local>) -> (i32, u32),
+{
+    if periods == 0 {
+        return;
+    }
+    let mut seen = HashSet::new();
+    for (i, t) in times.iter().enumerate() {
+        // 时间倒序，每个周期遇到的第一个就是该周期最新的备份
+        if seen.insert(period_of(t)) {
+            if seen.len() > periods {
+                break;
+            }
+            keep.insert(i);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupMetadata;
+    use chrono::TimeZone;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    /// `times` 需按时间倒序
+    fn backups(times: &[DateTime<Local>]) -> Vec<BackupInfo> {
+        times
+            .iter()
+            .map(|t| BackupInfo {
+                metadata: BackupMetadata {
+                    backup_path: t.to_rfc3339(),
+                    original_path: "settings.json".to_string(),
+                    timestamp: t.timestamp(),
+                    source_ide: "VS Code".to_string(),
+                    target_ide: "Cursor".to_string(),
+                    file_type: "settings".to_string(),
+                    file_size: 0,
+                    content_hash: String::new(),
+                    stored_size: 0,
+                    reason: "sync".to_string(),
+                    record_id: None,
+                },
+                formatted_time: String::new(),
+                size_mb: 0.0,
+            })
+            .collect()
+    }
+
+    fn policy(keep_last: usize) -> RetentionPolicy {
+        RetentionPolicy { keep_last, ..RetentionPolicy::default() }
+    }
+
+    fn expired(times: &[DateTime<Local>], policy: &RetentionPolicy, now: DateTime<Local>) -> Vec<DateTime<Local>> {
+        select_expired(&backups(times), policy, now)
+            .iter()
+            .map(|b| DateTime::parse_from_rfc3339(&b.metadata.backup_path).unwrap().with_timezone(&Local))
+            .collect()
+    }
+
+    #[test]
+    fn keep_last_keeps_the_newest_backups() {
+        let times: Vec<_> = (0..5).rev().map(|h| local(2026, 10, 19, h, 0)).collect();
+        let now = local(2026, 10, 19, 12, 0);
+        assert_eq!(expired(&times, &policy(2), now), times[2..].to_vec());
+    }
+
+    #[test]
+    fn newest_backup_is_kept_even_with_keep_last_zero() {
+        let times = [local(2026, 10, 19, 1, 0), local(2026, 10, 18, 1, 0)];
+        let now = local(2026, 10, 19, 12, 0);
+        assert_eq!(expired(&times, &policy(0), now), vec![times[1]]);
+    }
+
+    #[test]
+    fn keep_days_keeps_everything_inside_the_window() {
+        let now = local(2026, 10, 19, 12, 0);
+        let times = [
+            local(2026, 10, 19, 0, 0),
+            local(2026, 10, 17, 12, 0),
+            local(2026, 10, 16, 13, 0),
+            local(2026, 10, 16, 11, 0),
+            local(2026, 10, 11, 12, 0),
+        ];
+        let policy = RetentionPolicy { keep_days: 3, ..policy(1) };
+        assert_eq!(expired(&times, &policy, now), times[3..].to_vec());
+    }
+
+    #[test]
+    fn keep_daily_keeps_the_newest_backup_of_each_calendar_day() {
+        let now = local(2026, 10, 19, 12, 0);
+        let times = [
+            local(2026, 10, 19, 0, 10),
+            local(2026, 10, 18, 23, 50),
+            local(2026, 10, 18, 8, 0),
+            local(2026, 10, 17, 12, 0),
+        ];
+        let policy = RetentionPolicy { keep_daily: 2, ..policy(1) };
+        assert_eq!(expired(&times, &policy, now), times[2..].to_vec());
+    }
+
+    #[test]
+    fn keep_weekly_uses_iso_weeks_starting_on_monday() {
+        let now = local(2026, 10, 19, 12, 0);
+        // 2026-10-19 是星期一
+        let times = [
+            local(2026, 10, 19, 9, 0),
+            local(2026, 10, 18, 20, 0),
+            local(2026, 10, 17, 20, 0),
+            local(2026, 10, 11, 20, 0),
+        ];
+        let policy = RetentionPolicy { keep_weekly: 2, ..policy(1) };
+        assert_eq!(expired(&times, &policy, now), times[2..].to_vec());
+    }
+
+    #[test]
+    fn keep_monthly_keeps_the_newest_backup_of_each_month() {
+        let now = local(2026, 11, 1, 12, 0);
+        let times = [
+            local(2026, 11, 1, 0, 30),
+            local(2026, 10, 31, 23, 30),
+            local(2026, 10, 15, 12, 0),
+            local(2026, 9, 30, 12, 0),
+        ];
+        let policy = RetentionPolicy { keep_monthly: 2, ..policy(1) };
+        assert_eq!(expired(&times, &policy, now), times[2..].to_vec());
+    }
+
+    #[test]
+    fn rules_combine_as_a_union() {
+        let now = local(2026, 10, 19, 12, 0);
+        let times = [
+            local(2026, 10, 19, 9, 0),
+            local(2026, 10, 19, 8, 0),
+            local(2026, 10, 18, 8, 0),
+            local(2026, 9, 1, 8, 0),
+            local(2026, 8, 1, 8, 0),
+        ];
+        let policy = RetentionPolicy { keep_daily: 2, keep_monthly: 2, ..policy(1) };
+        assert_eq!(expired(&times, &policy, now), vec![times[1], times[4]]);
+    }
+}
@@ -0,0 +1,526 @@
+use crate::config;
+use crate::error::AppError;
+use crate::groups;
+use crate::history;
+use crate::ide;
+use crate::models::{IdeProfile, SyncOptions, SyncRecord, SyncStrategy, SyncTrigger};
+use crate::sync;
+use crate::t;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 最长多久重新读取一次任务列表，命令行或其他窗口修改的任务因此也会生效
+const POLL_INTERVAL: i64 = 30;
+
+/// 任务的运行时间
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum JobSchedule {
+    /// 每隔 N 分钟
+    Interval { minutes: u64 },
+    /// cron 表达式，按本地时间计算：5 段（分 时 日 月 周，0 和 7 为周日），
+    /// 或带秒的 6 段（星期为 1 周日到 7 周六）
+    Cron { expression: String },
+}
+
+impl JobSchedule {
+    fn describe(&self) -> String {
+        match self {
+            JobSchedule::Interval { minutes } => t!("schedule.interval", minutes = minutes),
+            JobSchedule::Cron { expression } => expression.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        match self {
+            JobSchedule::Interval { minutes: 0 } => Err(AppError::InvalidSchedule { schedule: self.describe() }),
+            JobSchedule::Interval { .. } => Ok(()),
+            JobSchedule::Cron { expression } => parse_cron(expression).map(|_| ()),
+        }
+    }
+
+    /// `after`（Unix 秒）之后的下一次运行时间
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        match self {
+            JobSchedule::Interval { minutes } => Some(after + *minutes as i64 * 60),
+            JobSchedule::Cron { expression } => {
+                let after: DateTime<Local> = DateTime::from_timestamp(after, 0)?.into();
+                parse_cron(expression).ok()?.after(&after).next().map(|t| t.timestamp())
+            }
+        }
+    }
+}
+
+fn parse_cron(expression: &str) -> Result<cron::Schedule, AppError> {
+    let expression = expression.trim();
+    let invalid = || AppError::InvalidSchedule { schedule: expression.to_string() };
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    // cron 库要求带秒，常见的 5 段写法补上第 0 秒，星期换成库的编号
+    let normalized = if let [minute, hour, day, month, day_of_week] = fields.as_slice() {
+        let day_of_week = convert_day_of_week(day_of_week).ok_or_else(invalid)?;
+        format!("0 {} {} {} {} {}", minute, hour, day, month, day_of_week)
+    } else {
+        expression.to_string()
+    };
+    cron::Schedule::from_str(&normalized).map_err(|_| invalid())
+}
+
+/// 5 段写法的星期按标准 cron 解释（0 和 7 为周日，1 为周一），而 cron 库为 1（周日）到 7（周六）。
+/// 数字写法展开成库的编号列表；英文缩写（`MON-FRI`）两边含义相同，原样保留。
+fn convert_day_of_week(field: &str) -> Option<String> {
+    let has_names = field.chars().any(|c| c.is_ascii_alphabetic());
+    if field == "*" || field == "?" || (has_names && !field.chars().any(|c| c.is_ascii_digit())) {
+        return Some(field.to_string());
+    }
+    if has_names {
+        // 数字和缩写混用时无法确定编号
+        return None;
+    }
+
+    let mut days: BTreeSet<u32> = BTreeSet::new();
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<u32>().ok().filter(|s| *s > 0)?)),
+            None => (item, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (0, 6),
+            Some((start, end)) => (start.parse::<u32>().ok()?, end.parse::<u32>().ok()?),
+            // `5/2` 表示从周五起每隔两天
+            None => {
+                let start = range.parse::<u32>().ok()?;
+                (start, if step.is_some() { 6 } else { start })
+            }
+        };
+        if start > end || end > 7 {
+            return None;
+        }
+        days.extend((start..=end).step_by(step.unwrap_or(1) as usize).map(|day| day % 7 + 1));
+    }
+    Some(days.iter().map(u32::to_string).collect::<Vec<_>>().join(","))
+}
+
+/// 任务运行时做什么
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum JobAction {
+    /// 从一个源同步到一个或多个目标
+    Sync { source_id: String, target_ids: Vec<String>, options: SyncOptions },
+    /// 运行一个同步组
+    Group { group_id: String },
+    /// 按名称运行一个同步预设
+    Preset { name: String },
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 定时任务
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledJob {
+    /// 创建时生成，新建时可省略
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub schedule: JobSchedule,
+    pub action: JobAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 上次运行的时间（Unix 秒）及结果，由调度器写入
+    #[serde(default)]
+    pub last_run: Option<i64>,
+    #[serde(default)]
+    pub last_success: Option<bool>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl ScheduledJob {
+    /// 下一次运行时间。从上次运行或上次修改任务起算，早于当前时间表示有错过的运行。
+    pub fn next_run(&self) -> Option<i64> {
+        if !self.enabled {
+            return None;
+        }
+        let since = self.last_run.unwrap_or(self.created_at).max(self.updated_at);
+        self.schedule.next_after(since)
+    }
+}
+
+/// 任务及其下一次运行时间
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub job: ScheduledJob,
+    pub next_run: Option<i64>,
+}
+
+/// 一次定时运行的结果
+#[derive(Debug, Serialize, Clone)]
+pub struct JobRun {
+    pub job_id: String,
+    pub job_name: String,
+    /// 历史记录 ID；同步没能开始时（例如 IDE 已卸载、同步组已删除）为 None
+    pub record_id: Option<String>,
+    pub success: bool,
+    pub error: Option<AppError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct JobStore {
+    #[serde(default)]
+    jobs: Vec<ScheduledJob>,
+}
+
+/// 读-改-写 jobs.json 期间持有
+static JOBS_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn get_jobs_file_path() -> PathBuf {
+    crate::data_dir().join("jobs.json")
+}
+
+fn load_store() -> JobStore {
+    match fs::read_to_string(get_jobs_file_path()) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => JobStore::default(),
+    }
+}
+
+fn save_store(store: &JobStore) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(store).map_err(AppError::serialization)?;
+    let path = get_jobs_file_path();
+    fs::write(&path, json).map_err(|e| AppError::io(&path, e))
+}
+
+pub fn list_jobs() -> Vec<JobInfo> {
+    load_store()
+        .jobs
+        .into_iter()
+        .map(|job| JobInfo { next_run: job.next_run(), job })
+        .collect()
+}
+
+/// 按 ID 或名称查找
+pub fn find_job(id_or_name: &str) -> Result<ScheduledJob, AppError> {
+    load_store()
+        .jobs
+        .into_iter()
+        .find(|j| j.id == id_or_name || j.name == id_or_name)
+        .ok_or_else(|| AppError::JobNotFound { id: id_or_name.to_string() })
+}
+
+/// 检查并规范化任务定义：去掉名称两端空白和重复的目标
+fn validate(job: &mut ScheduledJob, others: &[ScheduledJob]) -> Result<(), AppError> {
+    job.name = job.name.trim().to_string();
+    if job.name.is_empty() || others.iter().any(|j| j.id != job.id && j.name == job.name) {
+        return Err(AppError::InvalidJobName { name: job.name.clone() });
+    }
+    job.schedule.validate()?;
+
+    match &mut job.action {
+        JobAction::Sync { source_id, target_ids, options } => {
+            let mut unique: Vec<String> = Vec::new();
+            for id in target_ids.iter() {
+                if !unique.contains(id) {
+                    unique.push(id.clone());
+                }
+            }
+            *target_ids = unique;
+            // 一对多同步不支持三方合并
+            let fanout_bidirectional = target_ids.len() > 1 && options.strategy == SyncStrategy::Bidirectional;
+            if target_ids.is_empty() || target_ids.contains(source_id) || fanout_bidirectional {
+                return Err(AppError::InvalidJobTargets { name: job.name.clone() });
+            }
+        }
+        JobAction::Group { group_id } => {
+            groups::find_group(group_id)?;
+        }
+        JobAction::Preset { name } => {
+            config::find_preset(name)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn create_job(mut job: ScheduledJob) -> Result<ScheduledJob, AppError> {
+    let _guard = JOBS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    job.id = Uuid::new_v4().to_string();
+    validate(&mut job, &store.jobs)?;
+
+    let now = Local::now().timestamp();
+    job.created_at = now;
+    job.updated_at = now;
+    job.last_run = None;
+    job.last_success = None;
+    job.last_error = None;
+    store.jobs.push(job.clone());
+    save_store(&store)?;
+    Ok(job)
+}
+
+/// 按 `job.id` 替换已有的任务，保留创建时间和上次运行的结果
+pub fn update_job(mut job: ScheduledJob) -> Result<ScheduledJob, AppError> {
+    let _guard = JOBS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    validate(&mut job, &store.jobs)?;
+
+    let existing = store
+        .jobs
+        .iter_mut()
+        .find(|j| j.id == job.id)
+        .ok_or_else(|| AppError::JobNotFound { id: job.id.clone() })?;
+    job.created_at = existing.created_at;
+    job.updated_at = Local::now().timestamp();
+    job.last_run = existing.last_run;
+    job.last_success = existing.last_success;
+    job.last_error = existing.last_error.clone();
+    *existing = job.clone();
+    save_store(&store)?;
+    Ok(job)
+}
+
+/// 删除任务。它的历史记录保留。
+pub fn delete_job(job_id: &str) -> Result<(), AppError> {
+    let _guard = JOBS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    let before = store.jobs.len();
+    store.jobs.retain(|j| j.id != job_id);
+    if store.jobs.len() == before {
+        return Err(AppError::JobNotFound { id: job_id.to_string() });
+    }
+    save_store(&store)
+}
+
+fn save_run(job_id: &str, at: i64, run: &JobRun) {
+    let _guard = JOBS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = load_store();
+    if let Some(job) = store.jobs.iter_mut().find(|j| j.id == job_id) {
+        job.last_run = Some(at);
+        job.last_success = Some(run.success);
+        job.last_error = run.error.as_ref().map(|e| e.to_string());
+        if let Err(e) = save_store(&store) {
+            eprintln!("{}", t!("warn.job_state_failed", error = e));
+        }
+    }
+}
+
+/// 运行一次任务，结果写入历史（`trigger` 为 `Scheduled`）并记在任务上
+pub fn run_job(job: &ScheduledJob) -> JobRun {
+    let started = Local::now().timestamp();
+    let ides = ide::get_default_ides();
+    let record_id = history::new_record_id();
+
+    let outcome = run_action(&job.action, &ides, &record_id).map(|mut record| {
+        record.trigger = SyncTrigger::Scheduled;
+        record.job_id = Some(job.id.clone());
+        let success = record.status == "Success";
+        history::append_history(record);
+        success
+    });
+
+    let run = JobRun {
+        job_id: job.id.clone(),
+        job_name: job.name.clone(),
+        record_id: outcome.is_ok().then_some(record_id),
+        success: matches!(outcome, Ok(true)),
+        error: outcome.err(),
+    };
+    save_run(&job.id, started, &run);
+    run
+}
+
+/// 执行任务的同步，返回待写入的历史记录
+fn run_action(action: &JobAction, ides: &[IdeProfile], record_id: &str) -> Result<SyncRecord, AppError> {
+    match action {
+        JobAction::Group { group_id } => {
+            let group = groups::find_group(group_id)?;
+            let (source, _) = groups::resolve_ides(&group, ides)?;
+            let result = groups::run_group(&group, ides, record_id)?;
+            Ok(history::group_run_record(&group, &source, SyncTrigger::Scheduled, &result))
+        }
+        JobAction::Preset { name } => {
+            let preset = config::find_preset(name)?;
+            let (source, target) = config::resolve_ides(&preset, ides)?;
+            let result = config::run_preset(&preset, ides, record_id)?;
+            Ok(history::sync_record(&source, &target, &preset.options, &result))
+        }
+        JobAction::Sync { source_id, target_ids, options } => {
+            let find = |id: &String| {
+                ides.iter()
+                    .find(|i| &i.id == id)
+                    .cloned()
+                    .ok_or_else(|| AppError::IdeNotFound { id: id.clone() })
+            };
+            let source = find(source_id)?;
+            let targets = target_ids.iter().map(find).collect::<Result<Vec<_>, _>>()?;
+
+            if let [target] = targets.as_slice() {
+                let result = sync::perform_sync(source.clone(), target.clone(), options.clone(), record_id);
+                return Ok(history::sync_record(&source, target, options, &result));
+            }
+            let result = sync::perform_fanout_sync(source.clone(), targets, options.clone(), record_id)?;
+            Ok(history::fanout_record(&source, options, &result))
+        }
+    }
+}
+
+/// 后台调度器，丢弃时停止
+pub struct Scheduler {
+    stop: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 启动调度器。应用关闭期间错过的运行在启动后补跑一次（不逐次补跑）。
+/// 每次运行结束后调用 `on_run`。
+pub fn start(on_run: impl Fn(JobRun) + Send + 'static) -> Scheduler {
+    let (stop, receiver) = mpsc::channel();
+    let thread = thread::spawn(move || loop {
+        let now = Local::now().timestamp();
+        let mut next_wake = now + POLL_INTERVAL;
+        for info in list_jobs() {
+            match info.next_run {
+                Some(at) if at <= now => on_run(run_job(&info.job)),
+                Some(at) => next_wake = next_wake.min(at),
+                None => {}
+            }
+        }
+
+        let wait = (next_wake - Local::now().timestamp()).clamp(1, POLL_INTERVAL);
+        match receiver.recv_timeout(Duration::from_secs(wait as u64)) {
+            Err(RecvTimeoutError::Timeout) => {}
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    });
+
+    Scheduler { stop, thread: Some(thread) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone, Weekday};
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().timestamp()
+    }
+
+    fn cron(expression: &str) -> JobSchedule {
+        JobSchedule::Cron { expression: expression.to_string() }
+    }
+
+    fn weekday(timestamp: i64) -> Weekday {
+        DateTime::from_timestamp(timestamp, 0).unwrap().with_timezone(&Local).weekday()
+    }
+
+    #[test]
+    fn interval_counts_from_the_given_time() {
+        let schedule = JobSchedule::Interval { minutes: 90 };
+        assert_eq!(schedule.next_after(1_000), Some(1_000 + 90 * 60));
+        assert!(JobSchedule::Interval { minutes: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn weekdays_skip_the_weekend() {
+        // 2026-10-17 是周六
+        let saturday = local(2026, 10, 17, 10, 0);
+        assert_eq!(cron("0 9 * * 1-5").next_after(saturday), Some(local(2026, 10, 19, 9, 0)));
+
+        let friday_evening = local(2026, 10, 16, 10, 0);
+        assert_eq!(cron("0 9 * * 1-5").next_after(friday_evening), Some(local(2026, 10, 19, 9, 0)));
+    }
+
+    #[test]
+    fn zero_and_seven_are_sunday() {
+        let saturday = local(2026, 10, 17, 10, 0);
+        for expression in ["0 9 * * 0", "0 9 * * 7"] {
+            let next = cron(expression).next_after(saturday).unwrap();
+            assert_eq!(next, local(2026, 10, 18, 9, 0), "{}", expression);
+            assert_eq!(weekday(next), Weekday::Sun);
+        }
+    }
+
+    #[test]
+    fn lists_ranges_and_steps_use_standard_numbering() {
+        assert_eq!(convert_day_of_week("1-5").as_deref(), Some("2,3,4,5,6"));
+        assert_eq!(convert_day_of_week("5-7").as_deref(), Some("1,6,7"));
+        assert_eq!(convert_day_of_week("0,3").as_deref(), Some("1,4"));
+        assert_eq!(convert_day_of_week("*/2").as_deref(), Some("1,3,5,7"));
+        assert_eq!(convert_day_of_week("1/3").as_deref(), Some("2,5"));
+        assert_eq!(convert_day_of_week("MON-FRI").as_deref(), Some("MON-FRI"));
+        assert_eq!(convert_day_of_week("*").as_deref(), Some("*"));
+
+        let wednesday = local(2026, 10, 14, 12, 0);
+        let next = cron("30 8 * * 5,6").next_after(wednesday).unwrap();
+        assert_eq!(next, local(2026, 10, 16, 8, 30));
+        assert_eq!(weekday(next), Weekday::Fri);
+    }
+
+    #[test]
+    fn invalid_day_of_week_is_rejected() {
+        for expression in ["0 9 * * 8", "0 9 * * 5-2", "0 9 * * MON,3", "0 9 * * */0", "nope"] {
+            assert!(cron(expression).validate().is_err(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn six_field_expressions_are_passed_through() {
+        // 6 段写法沿用 cron 库的编号：2 为周一
+        let saturday = local(2026, 10, 17, 10, 0);
+        assert_eq!(cron("0 0 9 * * 2").next_after(saturday), Some(local(2026, 10, 19, 9, 0)));
+    }
+
+    #[test]
+    fn bidirectional_sync_jobs_allow_only_one_target() {
+        let job = |target_ids: &[&str], strategy: SyncStrategy| ScheduledJob {
+            id: String::new(),
+            name: "nightly".to_string(),
+            schedule: JobSchedule::Interval { minutes: 60 },
+            action: JobAction::Sync {
+                source_id: "vscode".to_string(),
+                target_ids: target_ids.iter().map(|id| id.to_string()).collect(),
+                options: SyncOptions {
+                    sync_settings: true,
+                    sync_extensions: false,
+                    sync_keybindings: true,
+                    sync_snippets: false,
+                    dry_run: false,
+                    strategy,
+                },
+            },
+            enabled: true,
+            last_run: None,
+            last_success: None,
+            last_error: None,
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        assert!(validate(&mut job(&["cursor"], SyncStrategy::Bidirectional), &[]).is_ok());
+        assert!(validate(&mut job(&["cursor", "cursor"], SyncStrategy::Bidirectional), &[]).is_ok());
+        assert!(validate(&mut job(&["cursor", "windsurf"], SyncStrategy::Merge), &[]).is_ok());
+        assert!(matches!(
+            validate(&mut job(&["cursor", "windsurf"], SyncStrategy::Bidirectional), &[]),
+            Err(AppError::InvalidJobTargets { .. })
+        ));
+    }
+}
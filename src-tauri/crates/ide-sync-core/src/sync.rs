@@ -0,0 +1,801 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Instant;
+use crate::models::{FanoutResult, FileOutcome, FileStatus, IdeProfile, SyncFilters, SyncOptions, SyncRecord, SyncResult, SyncStrategy, TargetRecord, TargetResult};
+use crate::backup;
+use crate::error::AppError;
+use crate::t;
+use jsonc_parser::{parse_to_serde_value, ParseOptions};
+use serde_json::Value;
+
+/// 源文件的内容，读取一次后应用到每个目标
+struct SourceFile {
+    content: String,
+    json: Value,
+}
+
+fn read_source_file(path: &Path) -> Result<SourceFile, AppError> {
+    let path = path.to_string_lossy().to_string();
+    let content = fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+    let json = parse_to_serde_value(&content, &ParseOptions::default())
+        .map_err(|_| AppError::InvalidJsonc { path })?
+        .unwrap_or(Value::Null);
+    Ok(SourceFile { content, json })
+}
+
+/// 源 IDE 中本次要同步的全部内容。未选择的部分为 None。
+struct SourceConfig {
+    settings: Option<Result<SourceFile, AppError>>,
+    /// 源 IDE 没有 keybindings.json 时为 None
+    keybindings: Option<Result<SourceFile, AppError>>,
+    snippets: Vec<(PathBuf, Result<SourceFile, AppError>)>,
+    /// dry run 时不获取
+    extensions: Option<Result<Vec<String>, AppError>>,
+}
+
+/// Drops the top-level settings excluded by `filters`. The file is rewritten
+/// from the remaining keys only when something was removed, so comments are
+/// kept otherwise.
+fn filter_settings(mut file: SourceFile, filters: &SyncFilters) -> Result<SourceFile, AppError> {
+    if let Value::Object(map) = &mut file.json {
+        let before = map.len();
+        map.retain(|key, _| !filters.excludes_setting(key));
+        if map.len() != before {
+            file.content = serde_json::to_string_pretty(&file.json).map_err(AppError::serialization)?;
+        }
+    }
+    Ok(file)
+}
+
+fn read_source(source: &IdeProfile, options: &SyncOptions, filters: &SyncFilters) -> SourceConfig {
+    let keybindings_path = crate::ide::get_keybindings_path(&source.config_path);
+    SourceConfig {
+        settings: options
+            .sync_settings
+            .then(|| read_source_file(Path::new(&source.config_path)).and_then(|f| filter_settings(f, filters))),
+        keybindings: (options.sync_keybindings && keybindings_path.exists())
+            .then(|| read_source_file(&keybindings_path)),
+        snippets: if options.sync_snippets {
+            crate::ide::list_snippet_files(&source.config_path)
+                .into_iter()
+                .filter(|path| {
+                    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                    !filters.excludes_snippet(&name)
+                })
+                .map(|path| {
+                    let file = read_source_file(&path);
+                    (path, file)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        },
+        extensions: (options.sync_extensions && !options.dry_run).then(|| {
+            list_extensions(source).map(|exts| exts.into_iter().filter(|e| !filters.excludes_extension(e)).collect())
+        }),
+    }
+}
+
+/// Runs one sync. `record_id` is the history record this run will be stored
+/// under; the snapshot and backups it creates are linked to it.
+pub fn perform_sync(source: IdeProfile, target: IdeProfile, options: SyncOptions, record_id: &str) -> SyncResult {
+    if options.strategy == SyncStrategy::Bidirectional {
+        return crate::bidirectional::perform_bidirectional_sync(&source, &target, &options, record_id);
+    }
+    let filters = SyncFilters::default();
+    let config = read_source(&source, &options, &filters);
+    apply_sync(&source, &config, &target, &options, &filters, record_id)
+}
+
+/// Syncs one source into several targets. The source is read once and the
+/// targets are written in parallel; each gets its own snapshot, all linked to
+/// the same history record `record_id`. Bidirectional mode is not supported,
+/// since every target would write back into the source.
+pub fn perform_fanout_sync(source: IdeProfile, targets: Vec<IdeProfile>, options: SyncOptions, record_id: &str) -> Result<FanoutResult, AppError> {
+    perform_filtered_fanout_sync(source, targets, options, &SyncFilters::default(), record_id)
+}
+
+/// Same as [`perform_fanout_sync`], leaving out whatever `filters` excludes.
+/// Excluded settings keep the target's own value, also when overwriting.
+pub fn perform_filtered_fanout_sync(
+    source: IdeProfile,
+    targets: Vec<IdeProfile>,
+    options: SyncOptions,
+    filters: &SyncFilters,
+    record_id: &str,
+) -> Result<FanoutResult, AppError> {
+    if options.strategy == SyncStrategy::Bidirectional {
+        return Err(AppError::UnsupportedStrategy { strategy: format!("{:?}", options.strategy) });
+    }
+    let started = Instant::now();
+    let config = read_source(&source, &options, filters);
+
+    // 各目标的文件互不重叠，备份索引有锁保护，可以并行
+    let targets: Vec<TargetResult> = thread::scope(|scope| {
+        let handles: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                let (source, config, options) = (&source, &config, &options);
+                scope.spawn(move || apply_sync(source, config, target, options, filters, record_id))
+            })
+            .collect();
+        targets
+            .iter()
+            .zip(handles)
+            .map(|(target, handle)| TargetResult {
+                target_id: target.id.clone(),
+                target_name: target.name.clone(),
+                result: handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)),
+            })
+            .collect()
+    });
+
+    Ok(FanoutResult {
+        record_id: record_id.to_string(),
+        success: targets.iter().all(|t| t.result.success),
+        targets,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn apply_sync(
+    source: &IdeProfile,
+    config: &SourceConfig,
+    target: &IdeProfile,
+    options: &SyncOptions,
+    filters: &SyncFilters,
+    record_id: &str,
+) -> SyncResult {
+    let started = Instant::now();
+    let mut log = Vec::new();
+    let mut success = true;
+
+    // 本次同步触及的文件，结束时汇总为一个快照
+    let mut snapshot_files = Vec::new();
+    let mut files = Vec::new();
+    let mut installed_extensions = Vec::new();
+    let mut failed_extensions = Vec::new();
+
+    log.push(t!("sync.start", source = source.name, target = target.name));
+    log.push(t!("sync.strategy", strategy = format!("{:?}", options.strategy)));
+
+    if let Some(src) = &config.settings {
+        log.push(t!("sync.section.settings"));
+        let result = src
+            .as_ref()
+            .map_err(AppError::clone)
+            .and_then(|src| {
+                let kept = match options.strategy {
+                    SyncStrategy::Overwrite => keep_excluded_settings(src, &target.config_path, filters)?,
+                    _ => None,
+                };
+                sync_file(kept.as_ref().unwrap_or(src), &target.config_path, options.dry_run, &options.strategy, &source.name, &target.name, "settings")
+            });
+        files.push(file_outcome("settings", &target.config_path, &result));
+        match result {
+            Ok((msg, file)) => {
+                log.push(msg);
+                snapshot_files.extend(file);
+            }
+            Err(e) => {
+                log.push(t!("sync.error.settings", error = e));
+                success = false;
+            }
+        }
+    }
+
+    if options.sync_keybindings {
+        log.push(t!("sync.section.keybindings"));
+        let tgt_kb = crate::ide::get_keybindings_path(&target.config_path);
+        
+        if let Some(src_kb) = &config.keybindings {
+             // Keybindings are usually arrays, so merge strategy might be different (append).
+             // For now, we reuse the same strategy (Top level array merge if array, or overwrite).
+             // Since keybindings.json is [ ... ], merging usually means appending.
+             // But if it's Overwrite, we replace.
+             let result = src_kb
+                 .as_ref()
+                 .map_err(AppError::clone)
+                 .and_then(|src| sync_file(src, &tgt_kb.to_string_lossy(), options.dry_run, &options.strategy, &source.name, &target.name, "keybindings"));
+             files.push(file_outcome("keybindings", &tgt_kb.to_string_lossy(), &result));
+             match result {
+                Ok((msg, file)) => {
+                    log.push(msg);
+                    snapshot_files.extend(file);
+                }
+                Err(e) => {
+                    log.push(t!("sync.error.keybindings", error = e));
+                    success = false;
+                }
+            }
+        } else {
+             log.push(t!("sync.keybindings_missing"));
+             files.push(FileOutcome {
+                 file_type: "keybindings".to_string(),
+                 path: tgt_kb.to_string_lossy().to_string(),
+                 status: FileStatus::Skipped,
+                 backup_path: None,
+                 message: t!("file.source_keybindings_missing"),
+                 error_code: None,
+             });
+        }
+    }
+
+    if options.sync_snippets {
+        log.push(t!("sync.section.snippets"));
+        let tgt_dir = crate::ide::get_snippets_dir(&target.config_path);
+
+        if config.snippets.is_empty() {
+            log.push(t!("sync.no_snippets"));
+        }
+        for (src_file, src) in &config.snippets {
+            let name = src_file.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+            let tgt_file = tgt_dir.join(name);
+            let result = src
+                .as_ref()
+                .map_err(AppError::clone)
+                .and_then(|src| sync_file(src, &tgt_file.to_string_lossy(), options.dry_run, &options.strategy, &source.name, &target.name, "snippets"));
+            files.push(file_outcome("snippets", &tgt_file.to_string_lossy(), &result));
+            match result {
+                Ok((msg, file)) => {
+                    log.push(msg);
+                    snapshot_files.extend(file);
+                }
+                Err(e) => {
+                    log.push(t!("sync.error.snippet", path = src_file.display(), error = e));
+                    success = false;
+                }
+            }
+        }
+    }
+
+    if options.sync_extensions {
+        log.push(t!("sync.section.extensions"));
+        match &config.extensions {
+            // 标准配置等没有 CLI 的目标，扩展写入清单文件
+            Some(extensions) if target.uses_extension_manifest() => {
+                let merge = options.strategy == SyncStrategy::Merge;
+                let result = extensions
+                    .as_ref()
+                    .map_err(AppError::clone)
+                    .and_then(|extensions| sync_extension_manifest(extensions, source, target, merge));
+                files.push(file_outcome("extensions", &target.extensions_path, &result));
+                match result {
+                    Ok((msg, file)) => {
+                        log.push(msg);
+                        snapshot_files.extend(file);
+                    }
+                    Err(e) => {
+                        log.push(t!("sync.error.extensions", error = e));
+                        success = false;
+                    }
+                }
+            }
+            Some(extensions) => {
+                let result = extensions
+                    .as_ref()
+                    .map_err(AppError::clone)
+                    .map(|extensions| sync_extensions_cli(extensions, source, target, &mut log));
+                match result {
+                    Ok((installed, failed)) => {
+                        log.push(t!("sync.extensions_done"));
+                        installed_extensions = installed;
+                        failed_extensions = failed;
+                    }
+                    Err(e) => {
+                        log.push(t!("sync.error.extensions", error = e));
+                        success = false;
+                    }
+                }
+            }
+            None => log.push(t!("sync.extensions_dry_run")),
+        }
+    }
+
+    let snapshot_id = record_snapshot(&source.name, &target.name, snapshot_files, record_id, &mut log);
+
+    SyncResult {
+        record_id: record_id.to_string(),
+        success,
+        log,
+        snapshot_id,
+        installed_extensions,
+        failed_extensions,
+        files,
+        duration_ms: started.elapsed().as_millis() as u64,
+        conflicts: Vec::new(),
+        source_installed_extensions: Vec::new(),
+    }
+}
+
+/// Records the files a run wrote as one snapshot linked to `record_id`, then
+/// applies the retention policy to the backups it created.
+pub(crate) fn record_snapshot(
+    source_ide: &str,
+    target_ide: &str,
+    snapshot_files: Vec<backup::SnapshotFile>,
+    record_id: &str,
+    log: &mut Vec<String>,
+) -> Option<String> {
+    let backed_up: Vec<String> = snapshot_files
+        .iter()
+        .filter(|f| f.backup_path.is_some())
+        .map(|f| f.original_path.clone())
+        .collect();
+
+    let mut snapshot_id = None;
+    if !snapshot_files.is_empty() {
+        match backup::create_snapshot(source_ide, target_ide, snapshot_files, record_id) {
+            Ok(snapshot) => {
+                log.push(t!("sync.snapshot_recorded", id = snapshot.id));
+                snapshot_id = Some(snapshot.id);
+            }
+            Err(e) => log.push(t!("sync.snapshot_failed", error = e)),
+        }
+    }
+
+    // Apply the configured retention policy to the backups this run created
+    for path in &backed_up {
+        match backup::apply_retention(path) {
+            Ok(0) => {},
+            Ok(n) => log.push(t!("sync.retention_removed", count = n, path = path)),
+            Err(e) => log.push(t!("sync.retention_failed", path = path, error = e)),
+        }
+    }
+
+    snapshot_id
+}
+
+/// When overwriting, carries the target's values for excluded settings over
+/// into the source, so excluded keys are left alone instead of deleted.
+/// Returns `None` when there is nothing to keep.
+fn keep_excluded_settings(src: &SourceFile, tgt_path: &str, filters: &SyncFilters) -> Result<Option<SourceFile>, AppError> {
+    if filters.exclude_settings.is_empty() || !Path::new(tgt_path).exists() {
+        return Ok(None);
+    }
+    let tgt = read_source_file(Path::new(tgt_path))?;
+    let (Value::Object(tgt_map), Value::Object(src_map)) = (&tgt.json, &src.json) else {
+        return Ok(None);
+    };
+    let mut json = src_map.clone();
+    for (key, value) in tgt_map.iter().filter(|(key, _)| filters.excludes_setting(key)) {
+        json.insert(key.clone(), value.clone());
+    }
+    if json.len() == src_map.len() {
+        return Ok(None);
+    }
+    let json = Value::Object(json);
+    let content = serde_json::to_string_pretty(&json).map_err(AppError::serialization)?;
+    Ok(Some(SourceFile { content, json }))
+}
+
+/// Summarizes one `sync_file` call for the history record.
+pub(crate) fn file_outcome(file_type: &str, path: &str, result: &Result<(String, Option<backup::SnapshotFile>), AppError>) -> FileOutcome {
+    let (status, backup_path, message) = match result {
+        Ok((msg, Some(file))) => (FileStatus::Synced, file.backup_path.clone(), msg.clone()),
+        Ok((msg, None)) => (FileStatus::DryRun, None, msg.clone()),
+        Err(e) => (FileStatus::Failed, None, e.to_string()),
+    };
+    FileOutcome {
+        file_type: file_type.to_string(),
+        path: path.to_string(),
+        status,
+        backup_path,
+        message,
+        error_code: result.as_ref().err().map(|e| e.code().to_string()),
+    }
+}
+
+fn sync_file(
+    src: &SourceFile, 
+    tgt_path: &str, 
+    dry_run: bool, 
+    strategy: &SyncStrategy,
+    source_ide: &str,
+    target_ide: &str,
+    file_type: &str,
+) -> Result<(String, Option<backup::SnapshotFile>), AppError> {
+    let final_content_str = if matches!(strategy, SyncStrategy::Merge) && Path::new(tgt_path).exists() {
+        // Read Target for merging
+        let tgt_content = fs::read_to_string(tgt_path).map_err(|e| AppError::io(tgt_path, e))?;
+        let mut tgt_json: Value = parse_to_serde_value(&tgt_content, &ParseOptions::default())
+            .map_err(|_| AppError::InvalidJsonc { path: tgt_path.to_string() })?
+            .unwrap_or(Value::Null);
+        
+        // Disable "smart merge" for arrays for now, just complex objects (settings).
+        merge_json_values(&mut tgt_json, &src.json);
+        
+        // Convert back to string (Pretty printed)
+        serde_json::to_string_pretty(&tgt_json).map_err(AppError::serialization)?
+    } else {
+        // Overwrite or file doesn't exist -> Use source as is (but prettified/cleaned)
+        // OR simply copy raw content to preserve comments?
+        // If Overwrite, users usually prefer raw content copy to keep comments.
+        // But if we want to valid JSONC, maybe keeping raw is better.
+        // Let's stick to raw copy for Overwrite to preserve comments.
+        src.content.clone()
+    };
+
+    if dry_run {
+        return Ok((t!("file.dry_run", path = tgt_path, strategy = format!("{:?}", strategy)), None));
+    }
+
+    let snapshot_file = write_with_backup(tgt_path, &final_content_str, source_ide, target_ide, file_type)?;
+    Ok((
+        t!("file.synced", path = tgt_path, strategy = format!("{:?}", strategy)),
+        Some(snapshot_file),
+    ))
+}
+
+/// Backs up `tgt_path` if it exists, then writes `content` to it. Returns the
+/// snapshot entry describing the write.
+///
+/// If the backup fails the file is left untouched and the error is returned:
+/// a snapshot entry without a backup means the file did not exist before, and
+/// restoring it would delete the user's file.
+pub(crate) fn write_with_backup(
+    tgt_path: &str,
+    content: &str,
+    source_ide: &str,
+    target_ide: &str,
+    file_type: &str,
+) -> Result<backup::SnapshotFile, AppError> {
+    let backup_path = if Path::new(tgt_path).exists() {
+        let metadata = backup::create_timestamped_backup(tgt_path, source_ide, target_ide, file_type, "sync")?;
+        Some(metadata.backup_path)
+    } else {
+        None
+    };
+    let snapshot_file = backup::SnapshotFile {
+        original_path: tgt_path.to_string(),
+        file_type: file_type.to_string(),
+        backup_path,
+        synced_hash: Some(backup::hash_content(content.as_bytes())),
+        deleted: false,
+        ide: Some(target_ide.to_string()),
+    };
+
+    // Write (snippets dir may not exist on the target yet)
+    if let Some(parent) = Path::new(tgt_path).parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+    }
+    fs::write(tgt_path, content).map_err(|e| AppError::io(tgt_path, e))?;
+    Ok(snapshot_file)
+}
+
+// Recursive shallow merge for JSON objects
+fn merge_json_values(target: &mut Value, source: &Value) {
+    match (target, source) {
+        (Value::Object(tgt_map), Value::Object(src_map)) => {
+            for (k, v) in src_map {
+                // If key exists in target and both are objects, recurse.
+                if let Some(tgt_v) = tgt_map.get_mut(k) {
+                    if tgt_v.is_object() && v.is_object() {
+                        merge_json_values(tgt_v, v);
+                    } else {
+                        // Otherwise overwrite value
+                        *tgt_v = v.clone();
+                    }
+                } else {
+                    // Key doesn't exist, insert
+                    tgt_map.insert(k.clone(), v.clone());
+                }
+            }
+        }
+        (Value::Array(tgt_arr), Value::Array(src_arr)) => {
+            // For arrays (like keybindings), we append simplified.
+            // Avoid duplicates? complex to check.
+            // Simple append for now.
+             for item in src_arr {
+                 if !tgt_arr.contains(item) {
+                     tgt_arr.push(item.clone());
+                 }
+             }
+        }
+        // For other types (or mismatch), we assume source wins if we called this function.
+        // But in the recursive branch above, we only called it if both are objects.
+        // This function signature is a bit weird for top-level calling without checks.
+        // Correct usage: only call if you intend to merge source INTO target.
+        (t, s) => {
+            *t = s.clone();
+        }
+    }
+}
+
+/// Get the full CLI path for an IDE executable
+fn get_cli_path(executable: &str) -> String {
+    #[cfg(target_os = "macos")]
+    {
+        // macOS: CLI tools are inside .app bundles
+        let app_paths: Vec<(&str, &str)> = vec![
+            ("code", "/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code"),
+            ("code-insiders", "/Applications/Visual Studio Code - Insiders.app/Contents/Resources/app/bin/code-insiders"),
+            ("codium", "/Applications/VSCodium.app/Contents/Resources/app/bin/codium"),
+            ("cursor", "/Applications/Cursor.app/Contents/Resources/app/bin/cursor"),
+            ("cursor-nightly", "/Applications/Cursor Nightly.app/Contents/Resources/app/bin/cursor-nightly"),
+            ("windsurf", "/Applications/Windsurf.app/Contents/Resources/app/bin/windsurf"),
+            ("antigravity", "/Applications/Antigravity.app/Contents/Resources/app/bin/antigravity"),
+            ("kiro", "/Applications/Kiro.app/Contents/Resources/app/bin/kiro"),
+            ("lingma", "/Applications/Lingma.app/Contents/Resources/app/bin/lingma"),
+            ("trae", "/Applications/Trae.app/Contents/Resources/app/bin/trae"),
+            ("positron", "/Applications/Positron.app/Contents/Resources/app/bin/positron"),
+            ("codeium", "/Applications/Codeium.app/Contents/Resources/app/bin/codeium"),
+            ("code-oss", "/Applications/Code - OSS.app/Contents/Resources/app/bin/code-oss"),
+        ];
+
+        for (name, path) in &app_paths {
+            if executable == *name && Path::new(path).exists() {
+                return path.to_string();
+            }
+        }
+
+        // Fallback: try the executable name directly (might be in PATH)
+        executable.to_string()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Windows: CLI tools are usually in LocalAppData or Program Files
+        if let Some(local_app_data) = dirs::data_local_dir() {
+            let possible_paths: Vec<(&str, Vec<std::path::PathBuf>)> = vec![
+                ("code", vec![
+                    local_app_data.join("Programs/Microsoft VS Code/bin/code.cmd"),
+                    std::path::PathBuf::from("C:/Program Files/Microsoft VS Code/bin/code.cmd"),
+                ]),
+                ("cursor", vec![
+                    local_app_data.join("Programs/cursor/resources/app/bin/cursor.cmd"),
+                    local_app_data.join("cursor/Cursor.exe"),
+                ]),
+                ("windsurf", vec![
+                    local_app_data.join("Programs/Windsurf/bin/windsurf.cmd"),
+                ]),
+            ];
+
+            for (name, paths) in possible_paths {
+                if executable == name {
+                    for path in paths {
+                        if path.exists() {
+                            return path.to_string_lossy().to_string();
+                        }
+                    }
+                }
+            }
+        }
+
+        // Fallback: try the executable name directly
+        executable.to_string()
+    }
+
+    #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+    {
+        // Linux: usually in PATH
+        executable.to_string()
+    }
+}
+
+/// Lists installed extensions, from the editor CLI or from the profile's manifest.
+pub fn list_extensions(ide: &IdeProfile) -> Result<Vec<String>, AppError> {
+    if ide.uses_extension_manifest() {
+        read_extension_manifest(&ide.extensions_path)
+    } else {
+        list_extensions_cli(ide)
+    }
+}
+
+/// An extension manifest is a JSON array of extension ids.
+pub fn read_extension_manifest(path: &str) -> Result<Vec<String>, AppError> {
+    if !Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+    serde_json::from_str(&content).map_err(|_| AppError::InvalidJsonc { path: path.to_string() })
+}
+
+fn list_extensions_cli(ide: &IdeProfile) -> Result<Vec<String>, AppError> {
+    let cli = get_cli_path(&ide.executable);
+    let output = Command::new(&cli)
+        .arg("--list-extensions")
+        .output()
+        .map_err(|_| AppError::CliNotFound { executable: ide.executable.clone() })?;
+
+    if !output.status.success() {
+        return Err(AppError::CliFailed {
+            executable: ide.executable.clone(),
+            detail: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Writes the source's extensions into the target's extension manifest
+/// instead of installing them. With `merge` the extensions already listed are
+/// kept. The manifest is backed up like any other synced file, so undo
+/// restores it.
+pub(crate) fn sync_extension_manifest(
+    extensions: &[String],
+    source: &IdeProfile,
+    target: &IdeProfile,
+    merge: bool,
+) -> Result<(String, Option<backup::SnapshotFile>), AppError> {
+    let mut manifest = if merge { read_extension_manifest(&target.extensions_path)? } else { Vec::new() };
+    for ext in extensions {
+        if !manifest.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+            manifest.push(ext.clone());
+        }
+    }
+    manifest.sort_by_key(|e| e.to_lowercase());
+
+    let json = serde_json::to_string_pretty(&manifest).map_err(AppError::serialization)?;
+    let file = write_with_backup(&target.extensions_path, &json, &source.name, &target.name, "extensions")?;
+    Ok((
+        t!("ext.manifest_written", count = manifest.len(), path = target.extensions_path),
+        Some(file),
+    ))
+}
+
+/// Installs the source's extensions on the target. Returns the ones that were
+/// not present on the target before (i.e. the ones this run added) and the
+/// ones that failed to install.
+pub(crate) fn sync_extensions_cli(extensions: &[String], source: &IdeProfile, target: &IdeProfile, log: &mut Vec<String>) -> (Vec<String>, Vec<String>) {
+    log.push(t!("ext.found", count = extensions.len(), ide = source.name));
+
+    // Remember what the target already had so undo only removes what we add.
+    // If the target can't be listed we treat everything as pre-existing.
+    let existing: Vec<String> = list_extensions_cli(target)
+        .unwrap_or_else(|_| extensions.to_vec())
+        .iter()
+        .map(|e| e.to_lowercase())
+        .collect();
+    let mut installed = Vec::new();
+    let mut failed = Vec::new();
+
+    let target_cli = get_cli_path(&target.executable);
+    for ext in extensions {
+        log.push(t!("ext.installing", ext = ext, ide = target.name));
+        let install = Command::new(&target_cli)
+            .arg("--install-extension")
+            .arg(ext)
+            .output();
+        
+        match install {
+            Ok(out) if out.status.success() => {
+                if !existing.contains(&ext.to_lowercase()) {
+                    installed.push(ext.clone());
+                }
+            },
+            Ok(out) => {
+                log.push(t!("ext.install_failed", ext = ext, error = String::from_utf8_lossy(&out.stderr)));
+                failed.push(ext.clone());
+            },
+            Err(e) => {
+                 log.push(t!("ext.install_exec_failed", error = e));
+                 failed.push(ext.clone());
+            }
+        }
+    }
+    (installed, failed)
+}
+
+/// Uninstalls extensions a sync added to `ide`. Returns false if any could
+/// not be removed, or if the IDE is no longer installed.
+fn uninstall_extensions(ide: Option<&IdeProfile>, extensions: &[String], log: &mut Vec<String>, failed_extensions: &mut Vec<String>) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+    let Some(ide) = ide else {
+        log.push(t!("undo.target_missing"));
+        return false;
+    };
+
+    let mut success = true;
+    let cli = get_cli_path(&ide.executable);
+    for ext in extensions {
+        log.push(t!("ext.uninstalling", ext = ext, ide = ide.name));
+        match Command::new(&cli).arg("--uninstall-extension").arg(ext).output() {
+            Ok(out) if out.status.success() => {},
+            Ok(out) => {
+                log.push(t!("ext.uninstall_failed", ext = ext, error = String::from_utf8_lossy(&out.stderr)));
+                failed_extensions.push(ext.clone());
+                success = false;
+            },
+            Err(e) => {
+                log.push(t!("ext.uninstall_exec_failed", error = e));
+                failed_extensions.push(ext.clone());
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// Reverts a sync run: restores every file from its snapshot and uninstalls the
+/// extensions that run added. Refuses if any file changed since or a backup is
+/// gone; a target that still fails to restore is logged and the remaining
+/// targets are reverted anyway. Grouped (one-to-many) records are reverted
+/// target by target. `ides` are the currently detected IDEs; `record_id`
+/// identifies the undo itself in history.
+pub fn undo_sync(record: &SyncRecord, ides: &[IdeProfile], record_id: &str) -> Result<SyncResult, AppError> {
+    let started = Instant::now();
+    let mut log = Vec::new();
+    let mut success = true;
+
+    if record.undone {
+        return Err(AppError::AlreadyUndone { id: record.id.clone() });
+    }
+
+    log.push(t!("undo.start", id = record.id, source = record.source_name, target = record.target_name));
+
+    let targets = if record.targets.is_empty() {
+        vec![TargetRecord {
+            target_id: record.target_id.clone(),
+            target_name: record.target_name.clone(),
+            success: record.status == "Success",
+            snapshot_id: record.snapshot_id.clone(),
+            installed_extensions: record.installed_extensions.clone(),
+        }]
+    } else {
+        record.targets.clone()
+    };
+
+    // 先确认所有目标都可以撤销，任何一个不满足都不做改动
+    for snapshot_id in targets.iter().filter_map(|t| t.snapshot_id.as_ref()) {
+        backup::check_snapshot_undoable(snapshot_id)?;
+    }
+
+    // 之后某个目标恢复失败时继续恢复其余目标，结果中保留已恢复的部分
+    let mut snapshot_ids = Vec::new();
+    let mut files = Vec::new();
+    let mut failed_extensions = Vec::new();
+    for part in &targets {
+        if let Some(record_snapshot) = &part.snapshot_id {
+            match backup::restore_snapshot(record_snapshot, record_id) {
+                Ok(outcome) => {
+                    log.push(outcome.message);
+                    files.extend(backup::snapshot_outcomes(&outcome.snapshot));
+                    snapshot_ids.push(outcome.snapshot.id);
+                }
+                Err(e) => {
+                    log.push(t!("undo.target_failed", target = part.target_name, error = e));
+                    success = false;
+                    continue;
+                }
+            }
+        } else {
+            log.push(t!("undo.no_files"));
+        }
+
+        let target = ides.iter().find(|i| i.id == part.target_id);
+        success &= uninstall_extensions(target, &part.installed_extensions, &mut log, &mut failed_extensions);
+    }
+
+    // 双向同步还向源安装了扩展。旧版记录没有 source_id，按名称查找
+    let source = if record.source_id.is_empty() {
+        ides.iter().find(|i| i.name == record.source_name)
+    } else {
+        ides.iter().find(|i| i.id == record.source_id)
+    };
+    success &= uninstall_extensions(source, &record.source_installed_extensions, &mut log, &mut failed_extensions);
+
+    crate::bidirectional::discard_base(&record.id);
+
+    Ok(SyncResult {
+        record_id: record_id.to_string(),
+        success,
+        log,
+        // 分组记录会恢复出多个快照，它们都关联到 record_id
+        snapshot_id: if snapshot_ids.len() == 1 { snapshot_ids.pop() } else { None },
+        installed_extensions: Vec::new(),
+        failed_extensions,
+        files,
+        duration_ms: started.elapsed().as_millis() as u64,
+        conflicts: Vec::new(),
+        source_installed_extensions: Vec::new(),
+    })
+}
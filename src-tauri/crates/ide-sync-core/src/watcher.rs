@@ -0,0 +1,209 @@
+use crate::backup;
+use crate::error::AppError;
+use crate::groups::{self, SyncGroup};
+use crate::history;
+use crate::ide;
+use crate::models::{IdeProfile, SyncTrigger};
+use crate::t;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 源文件最后一次变化后等待多久再同步。编辑器保存时常会连续写入多次。
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// 一次自动同步的结果
+#[derive(Debug, Serialize, Clone)]
+pub struct AutoRun {
+    pub group_id: String,
+    pub group_name: String,
+    /// 历史记录 ID；同步没能开始时（例如目标 IDE 已卸载）为 None
+    pub record_id: Option<String>,
+    pub success: bool,
+    pub error: Option<AppError>,
+}
+
+enum Message {
+    Fs(notify::Result<Event>),
+    Stop,
+}
+
+/// 开启了自动同步的组及其源 IDE
+struct Watched {
+    group: SyncGroup,
+    source: IdeProfile,
+}
+
+impl Watched {
+    /// `path` 是否是该组会同步的源文件
+    fn covers(&self, path: &Path) -> bool {
+        let options = &self.group.options;
+        let config = &self.source.config_path;
+        (options.sync_settings && path == Path::new(config))
+            || (options.sync_keybindings && path == ide::get_keybindings_path(config))
+            || (options.sync_snippets && path.parent() == Some(ide::get_snippets_dir(config).as_path()))
+    }
+}
+
+/// 后台监听开启了 `auto_sync` 的同步组的源 IDE，配置文件变化后自动运行该组。
+/// groups.json 变化时重新加载。丢弃时停止监听。
+pub struct AutoSyncWatcher {
+    sender: Sender<Message>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for AutoSyncWatcher {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Message::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// 开始监听。每次自动同步结束后（已写入历史）调用 `on_run`。
+pub fn start(on_run: impl Fn(AutoRun) + Send + 'static) -> Result<AutoSyncWatcher, AppError> {
+    let (sender, receiver) = mpsc::channel();
+    let fs_sender = sender.clone();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = fs_sender.send(Message::Fs(event));
+    })
+    .map_err(AppError::internal)?;
+
+    let thread = thread::spawn(move || {
+        let mut state = WatchState { watcher, dirs: HashSet::new(), watched: Vec::new() };
+        state.reload();
+        let groups_file = groups::get_groups_file_path();
+        // 组 ID -> (同步时间, 变化的文件)
+        let mut pending: HashMap<String, (Instant, HashSet<PathBuf>)> = HashMap::new();
+
+        loop {
+            let timeout = pending
+                .values()
+                .map(|(due, _)| due.saturating_duration_since(Instant::now()))
+                .min()
+                .unwrap_or(Duration::from_secs(3600));
+
+            match receiver.recv_timeout(timeout) {
+                Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                Ok(Message::Fs(Ok(event))) if !matches!(event.kind, EventKind::Access(_)) => {
+                    for path in &event.paths {
+                        if path == &groups_file {
+                            state.reload();
+                            pending.retain(|id, _| state.watched.iter().any(|w| &w.group.id == id));
+                            continue;
+                        }
+                        // 代码片段目录是后来创建的，需要开始监听它
+                        if path.is_dir() && state.watched.iter().any(|w| path == &ide::get_snippets_dir(&w.source.config_path)) {
+                            state.reload();
+                        }
+                        for watched in state.watched.iter().filter(|w| w.covers(path)) {
+                            let due = Instant::now() + DEBOUNCE;
+                            let entry = pending.entry(watched.group.id.clone()).or_insert_with(|| (due, HashSet::new()));
+                            entry.0 = due;
+                            entry.1.insert(path.clone());
+                        }
+                    }
+                }
+                Ok(Message::Fs(Ok(_))) | Err(RecvTimeoutError::Timeout) => {}
+                Ok(Message::Fs(Err(e))) => eprintln!("{}", t!("warn.watcher_error", error = e)),
+            }
+
+            let now = Instant::now();
+            let due: Vec<String> = pending
+                .iter()
+                .filter(|(_, (at, _))| *at <= now)
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in due {
+                let Some((_, paths)) = pending.remove(&id) else { continue };
+                // 同步、恢复写入的文件不再触发同步，避免互相指向的组来回同步
+                if paths.iter().all(|p| backup::is_own_write(&p.to_string_lossy())) {
+                    continue;
+                }
+                if let Some(watched) = state.watched.iter().find(|w| w.group.id == id) {
+                    on_run(run(&watched.group));
+                }
+            }
+        }
+    });
+
+    Ok(AutoSyncWatcher { sender, thread: Some(thread) })
+}
+
+struct WatchState {
+    watcher: RecommendedWatcher,
+    dirs: HashSet<PathBuf>,
+    watched: Vec<Watched>,
+}
+
+impl WatchState {
+    /// 重新读取同步组，按需增减监听的目录
+    fn reload(&mut self) {
+        let ides = ide::get_default_ides();
+        self.watched = groups::list_groups()
+            .into_iter()
+            .filter(|g| g.auto_sync)
+            .filter_map(|group| {
+                let source = ides.iter().find(|i| i.id == group.source_id)?.clone();
+                Some(Watched { group, source })
+            })
+            .collect();
+
+        // 只监听目录本身（不递归），编辑器保存时常先写临时文件再改名
+        let mut dirs: HashSet<PathBuf> = HashSet::new();
+        if let Some(data_dir) = groups::get_groups_file_path().parent() {
+            dirs.insert(data_dir.to_path_buf());
+        }
+        for watched in &self.watched {
+            let config = Path::new(&watched.source.config_path);
+            dirs.extend(config.parent().map(Path::to_path_buf));
+            let snippets = ide::get_snippets_dir(&watched.source.config_path);
+            if watched.group.options.sync_snippets && snippets.is_dir() {
+                dirs.insert(snippets);
+            }
+        }
+
+        for dir in self.dirs.difference(&dirs) {
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.dirs) {
+            if let Err(e) = self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                eprintln!("{}", t!("warn.watch_failed", path = dir.display(), error = e));
+            }
+        }
+        self.dirs = dirs;
+    }
+}
+
+/// 运行一次组同步并写入历史
+fn run(group: &SyncGroup) -> AutoRun {
+    let ides = ide::get_default_ides();
+    let record_id = history::new_record_id();
+    let outcome = groups::resolve_ides(group, &ides).and_then(|(source, _)| {
+        let result = groups::run_group(group, &ides, &record_id)?;
+        history::record_group_run(group, &source, SyncTrigger::Auto, &result);
+        Ok(result)
+    });
+
+    match outcome {
+        Ok(result) => AutoRun {
+            group_id: group.id.clone(),
+            group_name: group.name.clone(),
+            record_id: Some(result.record_id),
+            success: result.success,
+            error: None,
+        },
+        Err(e) => AutoRun {
+            group_id: group.id.clone(),
+            group_name: group.name.clone(),
+            record_id: None,
+            success: false,
+            error: Some(e),
+        },
+    }
+}
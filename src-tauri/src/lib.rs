@@ -1,13 +1,18 @@
-// use tauri::Emitter; // Unused
-pub mod models;
-pub mod ide;
-pub mod sync;
-pub mod history;
-pub mod backup;
+use tauri::{Emitter, Manager};
+pub use ide_sync_core::{archive, backup, config, diff, error, groups, history, i18n, ide, models, retention, scheduler, sync, watcher};
 
-use models::{IdeProfile, SyncOptions, SyncResult, SyncRecord};
-use backup::{BackupInfo, BackupStats};
-use std::time::{SystemTime, UNIX_EPOCH};
+use error::AppError;
+use models::{FanoutResult, IdeProfile, SyncOptions, SyncResult, SyncRecord, SyncTrigger};
+use backup::{BackupFilter, BackupInfo, BackupPage, BackupStats, RestoreOutcome, SnapshotInfo, VerifyReport};
+use retention::RetentionConfig;
+use diff::{BackupDiff, DiffAgainst};
+use archive::ArchiveManifest;
+use history::{HistoryPage, HistoryQuery};
+use groups::SyncGroup;
+use scheduler::{JobInfo, JobRun, ScheduledJob};
+use config::AppConfig;
+use i18n::Locale;
+use ide_sync_core::t;
 
 #[tauri::command]
 fn get_ides() -> Vec<IdeProfile> {
@@ -15,85 +20,394 @@ fn get_ides() -> Vec<IdeProfile> {
 }
 
 #[tauri::command]
-async fn execute_sync(source_id: String, target_id: String, options: SyncOptions, _app_handle: tauri::AppHandle) -> Result<SyncResult, String> {
+async fn execute_sync(source_id: String, target_id: String, options: SyncOptions, _app_handle: tauri::AppHandle) -> Result<SyncResult, AppError> {
     let ides = ide::get_default_ides();
     
-    let source = ides.iter().find(|i| i.id == source_id).ok_or("Source IDE not found")?;
-    let target = ides.iter().find(|i| i.id == target_id).ok_or("Target IDE not found")?;
-
-    let src_name = source.name.clone();
-    let tgt_name = target.name.clone();
+    let source = ides.iter().find(|i| i.id == source_id).ok_or_else(|| AppError::IdeNotFound { id: source_id.clone() })?;
+    let target = ides.iter().find(|i| i.id == target_id).ok_or_else(|| AppError::IdeNotFound { id: target_id.clone() })?;
 
     // Cloning for ownership
     let src_clone = source.clone();
     let tgt_clone = target.clone();
+    let opts_clone = options.clone();
+    let record_id = history::new_record_id();
     
     // Spawn blocking task
     let result = tauri::async_runtime::spawn_blocking(move || {
-        sync::perform_sync(src_clone, tgt_clone, options)
-    }).await.map_err(|e| e.to_string())?;
-
-    // Record History
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs() as i64;
-
-    let record = SyncRecord {
-        id: timestamp.to_string(), // Simple ID
-        timestamp,
-        source_name: src_name,
-        target_name: tgt_name,
-        status: if result.success { "Success".to_string() } else { "Failed".to_string() },
-        details: if result.success { 
-            "同步成功".to_string() 
-        } else { 
-            format!("同步失败，共 {} 条日志", result.log.len()) 
-        },
-    };
-    history::append_history(record);
+        sync::perform_sync(src_clone, tgt_clone, opts_clone, &record_id)
+    }).await.map_err(AppError::internal)?;
+
+    history::record_sync(source, target, &options, &result);
+
+    Ok(result)
+}
+
+/// Syncs one source into several targets, recorded as one grouped history entry
+#[tauri::command]
+async fn execute_sync_many(source_id: String, target_ids: Vec<String>, options: SyncOptions) -> Result<FanoutResult, AppError> {
+    let ides = ide::get_default_ides();
+    let source = ides.iter().find(|i| i.id == source_id).ok_or_else(|| AppError::IdeNotFound { id: source_id.clone() })?.clone();
+
+    let mut targets: Vec<IdeProfile> = Vec::new();
+    for target_id in &target_ids {
+        let target = ides.iter().find(|i| &i.id == target_id).ok_or_else(|| AppError::IdeNotFound { id: target_id.clone() })?;
+        if !targets.iter().any(|t| t.id == target.id) {
+            targets.push(target.clone());
+        }
+    }
+
+    let src_clone = source.clone();
+    let opts_clone = options.clone();
+    let record_id = history::new_record_id();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        sync::perform_fanout_sync(src_clone, targets, opts_clone, &record_id)
+    }).await.map_err(AppError::internal)??;
+
+    history::record_fanout(&source, &options, &result);
+
+    Ok(result)
+}
+
+#[tauri::command]
+fn list_sync_groups() -> Vec<SyncGroup> {
+    groups::list_groups()
+}
+
+#[tauri::command]
+fn create_sync_group(group: SyncGroup) -> Result<SyncGroup, AppError> {
+    groups::create_group(group)
+}
+
+#[tauri::command]
+fn update_sync_group(group: SyncGroup) -> Result<SyncGroup, AppError> {
+    groups::update_group(group)
+}
+
+#[tauri::command]
+fn delete_sync_group(group_id: String) -> Result<(), AppError> {
+    groups::delete_group(&group_id)
+}
+
+/// Runs a saved sync group; history records the run under the group
+#[tauri::command]
+async fn run_sync_group(group_id: String) -> Result<FanoutResult, AppError> {
+    let group = groups::find_group(&group_id)?;
+    let ides = ide::get_default_ides();
+    let (source, _) = groups::resolve_ides(&group, &ides)?;
+
+    let record_id = history::new_record_id();
+    let group_clone = group.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        groups::run_group(&group_clone, &ides, &record_id)
+    }).await.map_err(AppError::internal)??;
+
+    history::record_group_run(&group, &source, SyncTrigger::Manual, &result);
 
     Ok(result)
 }
 
 #[tauri::command]
-fn list_backups(file_path: String) -> Result<Vec<BackupInfo>, String> {
+fn list_scheduled_jobs() -> Vec<JobInfo> {
+    scheduler::list_jobs()
+}
+
+#[tauri::command]
+fn create_scheduled_job(job: ScheduledJob) -> Result<ScheduledJob, AppError> {
+    scheduler::create_job(job)
+}
+
+#[tauri::command]
+fn update_scheduled_job(job: ScheduledJob) -> Result<ScheduledJob, AppError> {
+    scheduler::update_job(job)
+}
+
+#[tauri::command]
+fn delete_scheduled_job(job_id: String) -> Result<(), AppError> {
+    scheduler::delete_job(&job_id)
+}
+
+/// Runs a scheduled job immediately; the next run is then counted from now
+#[tauri::command]
+async fn run_scheduled_job(job_id: String) -> Result<JobRun, AppError> {
+    let job = scheduler::find_job(&job_id)?;
+    tauri::async_runtime::spawn_blocking(move || scheduler::run_job(&job))
+        .await
+        .map_err(AppError::internal)
+}
+
+#[tauri::command]
+async fn undo_sync(record_id: String) -> Result<SyncResult, AppError> {
+    let undo_id = history::new_record_id();
+    let undone_id = record_id.clone();
+    let (target_name, result) = tauri::async_runtime::spawn_blocking(move || {
+        let mut target_name = String::new();
+        let result = history::undo_record(&undone_id, |record| {
+            target_name = record.target_name.clone();
+            sync::undo_sync(record, &ide::get_default_ides(), &undo_id)
+        });
+        result.map(|result| (target_name, result))
+    }).await.map_err(AppError::internal)??;
+
+    // 撤销分组记录时每个目标各恢复出一个快照
+    let snapshots: Vec<backup::Snapshot> = backup::list_snapshots()
+        .into_iter()
+        .map(|info| info.snapshot)
+        .filter(|s| s.record_id.as_deref() == Some(result.record_id.as_str()))
+        .collect();
+    history::record_undo(&snapshots, target_name, t!("history.undo", id = record_id), result.log.clone(), result.success);
+
+    Ok(result)
+}
+
+#[tauri::command]
+fn get_history() -> Vec<SyncRecord> {
+    history::read_history().into_iter().map(history::summarize).collect()
+}
+
+#[tauri::command]
+fn query_history(query: Option<HistoryQuery>) -> HistoryPage {
+    history::query_history(&query.unwrap_or_default())
+}
+
+#[tauri::command]
+fn get_history_entry(record_id: String) -> Result<SyncRecord, AppError> {
+    history::find_record(&record_id).ok_or_else(|| AppError::RecordNotFound { id: record_id.clone() })
+}
+
+#[tauri::command]
+fn list_backups(file_path: String) -> Result<Vec<BackupInfo>, AppError> {
     backup::list_backups(&file_path)
 }
 
 #[tauri::command]
-fn restore_backup(backup_path: String) -> Result<String, String> {
-    backup::restore_backup(&backup_path)
+fn list_all_backups(filter: Option<BackupFilter>) -> Result<BackupPage, AppError> {
+    backup::list_all_backups(&filter.unwrap_or_default())
 }
 
 #[tauri::command]
-fn delete_backup(backup_path: String) -> Result<String, String> {
+fn restore_backup(backup_path: String) -> Result<String, AppError> {
+    let RestoreOutcome { message, snapshot } = backup::restore_backup(&backup_path, &history::new_record_id())?;
+    history::record_restore(&snapshot, snapshot.source_ide.clone(), t!("history.restore_backup", path = backup_path), vec![message.clone()]);
+    Ok(message)
+}
+
+#[tauri::command]
+fn delete_backup(backup_path: String) -> Result<String, AppError> {
     backup::delete_backup(&backup_path)
 }
 
 #[tauri::command]
-fn cleanup_old_backups(file_path: String, keep_count: usize) -> Result<usize, String> {
+fn cleanup_old_backups(file_path: String, keep_count: usize) -> Result<usize, AppError> {
     backup::cleanup_old_backups(&file_path, keep_count)
 }
 
 #[tauri::command]
-fn get_backup_stats(file_path: String) -> Result<BackupStats, String> {
+fn get_backup_stats(file_path: String) -> Result<BackupStats, AppError> {
     backup::get_backup_stats(&file_path)
 }
 
+#[tauri::command]
+async fn verify_backups() -> Result<VerifyReport, AppError> {
+    tauri::async_runtime::spawn_blocking(backup::verify_backups)
+        .await
+        .map_err(AppError::internal)
+}
+
+#[tauri::command]
+fn diff_backup(backup_path: String, against: DiffAgainst) -> Result<BackupDiff, AppError> {
+    diff::diff_backup(&backup_path, &against)
+}
+
+#[tauri::command]
+fn get_retention_config() -> RetentionConfig {
+    retention::load_config()
+}
+
+#[tauri::command]
+fn set_retention_config(config: RetentionConfig) -> Result<(), AppError> {
+    retention::save_config(&config)?;
+    history::compact_history().map(|_| ())
+}
+
+#[tauri::command]
+fn get_config() -> AppConfig {
+    config::load_config()
+}
+
+#[tauri::command]
+fn set_config(config: AppConfig) -> Result<AppConfig, AppError> {
+    config::save_config(config)
+}
+
+/// Moves the preferences older versions kept in the webview's localStorage into config.json
+#[tauri::command]
+fn import_legacy_settings(settings: serde_json::Value) -> Result<AppConfig, AppError> {
+    config::import_legacy_settings(settings)
+}
+
+/// Runs a saved sync preset by name
+#[tauri::command]
+async fn run_sync_preset(name: String) -> Result<SyncResult, AppError> {
+    let preset = config::find_preset(&name)?;
+    let ides = ide::get_default_ides();
+    let (source, target) = config::resolve_ides(&preset, &ides)?;
+
+    let record_id = history::new_record_id();
+    let preset_clone = preset.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        config::run_preset(&preset_clone, &ides, &record_id)
+    }).await.map_err(AppError::internal)??;
+
+    history::record_sync(&source, &target, &preset.options, &result);
+
+    Ok(result)
+}
+
+#[tauri::command]
+fn get_locale() -> Locale {
+    i18n::current_locale()
+}
+
+#[tauri::command]
+fn set_locale(locale: Locale) -> Result<(), AppError> {
+    i18n::set_locale(locale)
+}
+
+#[tauri::command]
+fn preview_cleanup(file_path: String) -> Result<Vec<BackupInfo>, AppError> {
+    backup::preview_cleanup(&file_path)
+}
+
+#[tauri::command]
+fn list_snapshots() -> Vec<SnapshotInfo> {
+    backup::list_snapshots()
+}
+
+#[tauri::command]
+fn restore_snapshot(snapshot_id: String) -> Result<String, AppError> {
+    let RestoreOutcome { message, snapshot } = backup::restore_snapshot(&snapshot_id, &history::new_record_id())?;
+    history::record_restore(&snapshot, snapshot.source_ide.clone(), t!("history.restore_snapshot", id = snapshot_id), vec![message.clone()]);
+    Ok(message)
+}
+
+#[tauri::command]
+fn delete_snapshot(snapshot_id: String) -> Result<String, AppError> {
+    backup::delete_snapshot(&snapshot_id)
+}
+
+#[tauri::command]
+async fn export_snapshot(snapshot_id: String, dest_path: String) -> Result<ArchiveManifest, AppError> {
+    tauri::async_runtime::spawn_blocking(move || archive::export_snapshot(&snapshot_id, &dest_path))
+        .await
+        .map_err(AppError::internal)?
+}
+
+#[tauri::command]
+async fn export_profile(ide_id: String, dest_path: String) -> Result<ArchiveManifest, AppError> {
+    let profile = ide::get_default_ides()
+        .into_iter()
+        .find(|i| i.id == ide_id)
+        .ok_or_else(|| AppError::IdeNotFound { id: ide_id.clone() })?;
+    tauri::async_runtime::spawn_blocking(move || archive::export_profile(&profile, &dest_path))
+        .await
+        .map_err(AppError::internal)?
+}
+
+#[tauri::command]
+fn inspect_archive(archive_path: String) -> Result<ArchiveManifest, AppError> {
+    archive::read_archive(&archive_path).map(|a| a.manifest)
+}
+
+#[tauri::command]
+async fn import_archive(archive_path: String, target_id: String, options: SyncOptions) -> Result<SyncResult, AppError> {
+    let target = ide::get_default_ides()
+        .into_iter()
+        .find(|i| i.id == target_id)
+        .ok_or_else(|| AppError::IdeNotFound { id: target_id.clone() })?;
+    let source = archive::read_archive(&archive_path).map(|a| archive::source_profile(&a.manifest))?;
+
+    let tgt_clone = target.clone();
+    let opts_clone = options.clone();
+    let record_id = history::new_record_id();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        archive::import_archive(&archive_path, tgt_clone, opts_clone, &record_id)
+    }).await.map_err(AppError::internal)??;
+
+    history::record_sync(&source, &target, &options, &result);
+
+    Ok(result)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Err(e) = history::compact_history() {
+        eprintln!("{}", t!("warn.history_compaction_failed", error = e));
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .setup(|app| {
+            // 开启了自动同步的组：源配置变化后在后台同步，并通知前端刷新
+            let handle = app.handle().clone();
+            match watcher::start(move |run| {
+                let _ = handle.emit("auto-sync", run);
+            }) {
+                Ok(auto_sync) => {
+                    app.manage(auto_sync);
+                }
+                Err(e) => eprintln!("{}", t!("warn.auto_sync_unavailable", error = e)),
+            }
+
+            // 定时任务：启动时补跑关闭期间错过的运行
+            let handle = app.handle().clone();
+            app.manage(scheduler::start(move |run| {
+                let _ = handle.emit("scheduled-sync", run);
+            }));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_ides,
             execute_sync,
+            execute_sync_many,
+            list_sync_groups,
+            create_sync_group,
+            update_sync_group,
+            delete_sync_group,
+            run_sync_group,
+            list_scheduled_jobs,
+            create_scheduled_job,
+            update_scheduled_job,
+            delete_scheduled_job,
+            run_scheduled_job,
+            undo_sync,
+            get_history,
+            query_history,
+            get_history_entry,
             list_backups,
+            list_all_backups,
             restore_backup,
             delete_backup,
             cleanup_old_backups,
-            get_backup_stats
+            get_backup_stats,
+            diff_backup,
+            verify_backups,
+            get_retention_config,
+            set_retention_config,
+            get_config,
+            set_config,
+            import_legacy_settings,
+            run_sync_preset,
+            get_locale,
+            set_locale,
+            preview_cleanup,
+            list_snapshots,
+            restore_snapshot,
+            delete_snapshot,
+            export_snapshot,
+            export_profile,
+            inspect_archive,
+            import_archive
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
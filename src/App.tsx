@@ -4,19 +4,23 @@ import { Dashboard } from "./components/dashboard/Dashboard";
 
 import { SettingsView } from "./components/settings/SettingsView";
 import { invoke } from "@tauri-apps/api/core";
-import { IdeProfile, SyncOptions, AppSettings, DEFAULT_SETTINGS } from "./types";
+import { IdeProfile, SyncOptions, AppConfig, AppSettings, DEFAULT_SETTINGS } from "./types";
 
-// 从 localStorage 加载设置
-function loadSettings(): AppSettings {
-  try {
-    const saved = localStorage.getItem('ide-sync-settings');
-    if (saved) {
-      return { ...DEFAULT_SETTINGS, ...JSON.parse(saved) };
+// 旧版把设置保存在 localStorage 中，首次启动时迁移到后端的 config.json
+const LEGACY_SETTINGS_KEY = 'ide-sync-settings';
+
+async function loadConfig(): Promise<AppConfig> {
+  const legacy = localStorage.getItem(LEGACY_SETTINGS_KEY);
+  if (legacy) {
+    try {
+      const config = await invoke<AppConfig>("import_legacy_settings", { settings: JSON.parse(legacy) });
+      localStorage.removeItem(LEGACY_SETTINGS_KEY);
+      return config;
+    } catch (e) {
+      console.error('Failed to import legacy settings:', e);
     }
-  } catch (e) {
-    console.error('Failed to load settings:', e);
   }
-  return DEFAULT_SETTINGS;
+  return invoke<AppConfig>("get_config");
 }
 
 function App() {
@@ -24,30 +28,37 @@ function App() {
   const [ides, setIdes] = useState<IdeProfile[]>([]);
   const [loading, setLoading] = useState(false);
 
-  // 设置状态
-  const [settings, setSettings] = useState<AppSettings>(loadSettings);
+  // 设置状态，启动后从后端加载
+  const [settings, setSettingsState] = useState<AppSettings>(DEFAULT_SETTINGS);
 
   // Lifted State - 使用设置中的默认值
   const [sourceId, setSourceId] = useState<string>("");
-  const [targetId, setTargetId] = useState<string>("");
+  const [targetIds, setTargetIds] = useState<string[]>([]);
   const [logs, setLogs] = useState<string[]>([]);
-  const [options, setOptions] = useState<SyncOptions>({
-    sync_settings: settings.defaultSyncSettings,
-    sync_extensions: settings.defaultSyncExtensions,
-    sync_keybindings: settings.defaultSyncKeybindings,
-    dry_run: false,
-    strategy: settings.defaultStrategy,
-  });
-
-  // 当设置变更时保存到 localStorage
-  useEffect(() => {
-    localStorage.setItem('ide-sync-settings', JSON.stringify(settings));
-  }, [settings]);
+  const [options, setOptions] = useState<SyncOptions>(defaultOptions(DEFAULT_SETTINGS));
 
   useEffect(() => {
+    loadConfig()
+      .then((config) => {
+        setSettingsState(config.settings);
+        setOptions(defaultOptions(config.settings));
+      })
+      .catch((e) => console.error('Failed to load settings:', e));
     loadIdes();
   }, []);
 
+  // 保存设置时重新读取配置，保留命令行等其他地方修改的预设
+  async function setSettings(next: AppSettings) {
+    setSettingsState(next);
+    try {
+      const config = await invoke<AppConfig>("get_config");
+      const saved = await invoke<AppConfig>("set_config", { config: { ...config, settings: next } });
+      setSettingsState(saved.settings);
+    } catch (e) {
+      console.error('Failed to save settings:', e);
+    }
+  }
+
   async function loadIdes() {
     setLoading(true);
     try {
@@ -69,8 +80,8 @@ function App() {
           onRefresh={loadIdes}
           sourceId={sourceId}
           setSourceId={setSourceId}
-          targetId={targetId}
-          setTargetId={setTargetId}
+          targetIds={targetIds}
+          setTargetIds={setTargetIds}
           options={options}
           setOptions={setOptions}
           logs={logs}
@@ -91,4 +102,15 @@ function App() {
   );
 }
 
+function defaultOptions(settings: AppSettings): SyncOptions {
+  return {
+    sync_settings: settings.default_sync_settings,
+    sync_extensions: settings.default_sync_extensions,
+    sync_keybindings: settings.default_sync_keybindings,
+    sync_snippets: settings.default_sync_snippets,
+    dry_run: false,
+    strategy: settings.default_strategy,
+  };
+}
+
 export default App;
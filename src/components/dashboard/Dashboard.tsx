@@ -1,10 +1,10 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { IdeProfile, SyncOptions, SyncResult } from "../../types";
-import { Box, Settings, Terminal, CheckCircle, AlertTriangle, RefreshCw } from "lucide-react";
+import { FanoutResult, IdeProfile, SyncOptions, SyncResult } from "../../types";
+import { Box, Settings, FileCode, Terminal, CheckCircle, AlertTriangle, RefreshCw } from "lucide-react";
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "../ui/card";
 import { Button } from "../ui/button";
-import { cn } from "../../lib/utils";
+import { cn, errorMessage } from "../../lib/utils";
 
 interface DashboardProps {
   ides: IdeProfile[];
@@ -13,8 +13,8 @@ interface DashboardProps {
   // State lifted to App
   sourceId: string;
   setSourceId: (id: string) => void;
-  targetId: string;
-  setTargetId: (id: string) => void;
+  targetIds: string[];
+  setTargetIds: (ids: string[]) => void;
   options: SyncOptions;
   setOptions: (opt: SyncOptions) => void;
   logs: string[];
@@ -24,7 +24,7 @@ interface DashboardProps {
 export function Dashboard({
   ides, loading, onRefresh,
   sourceId, setSourceId,
-  targetId, setTargetId,
+  targetIds, setTargetIds,
   options, setOptions,
   logs, setLogs
 }: DashboardProps) {
@@ -33,34 +33,58 @@ export function Dashboard({
   // Auto-select logic moved to App or kept here but only if empty
   useEffect(() => {
     if (ides.length >= 1 && !sourceId) setSourceId(ides[0].id);
-    if (ides.length >= 2 && !targetId) setTargetId(ides[1].id);
-  }, [ides, sourceId, targetId, setSourceId, setTargetId]);
+    if (ides.length >= 2 && targetIds.length === 0) setTargetIds([ides[1].id]);
+  }, [ides, sourceId, targetIds, setSourceId, setTargetIds]);
+
+  function toggleTarget(id: string) {
+    setTargetIds(targetIds.includes(id) ? targetIds.filter(t => t !== id) : [...targetIds, id]);
+  }
 
   async function handleSync() {
-    if (!sourceId || !targetId) return;
-    if (sourceId === targetId) {
+    if (!sourceId || targetIds.length === 0) return;
+    if (targetIds.includes(sourceId)) {
       setLogs(prev => [...prev, "错误: 源 (Source) 和 目标 (Target) 不能相同。"]);
       return;
     }
+    if (options.strategy === 'Bidirectional' && targetIds.length > 1) {
+      setLogs(prev => [...prev, "错误: 双向同步只能选择一个目标。"]);
+      return;
+    }
 
     setSyncing(true);
     setLogs(["开始同步流程..."]);
 
     try {
-      const result = await invoke<SyncResult>("execute_sync", {
-        sourceId,
-        targetId,
-        options,
-      });
-
-      setLogs(result.log);
-      if (result.success) {
+      // 多个目标时一次读取源配置并行写入，历史中记为一条分组记录
+      let success: boolean;
+      if (targetIds.length === 1) {
+        const result = await invoke<SyncResult>("execute_sync", {
+          sourceId,
+          targetId: targetIds[0],
+          options,
+        });
+        setLogs(result.log);
+        success = result.success;
+      } else {
+        const result = await invoke<FanoutResult>("execute_sync_many", {
+          sourceId,
+          targetIds,
+          options,
+        });
+        setLogs(result.targets.flatMap(t => [
+          ...t.result.log,
+          t.result.success ? `✅ ${t.target_name} 同步成功` : `❌ ${t.target_name} 同步失败`,
+        ]));
+        success = result.success;
+      }
+
+      if (success) {
         setLogs(prev => [...prev, "✅ 同步成功完成!"]);
       } else {
         setLogs(prev => [...prev, "❌ 同步失败，请检查日志。"]);
       }
     } catch (e) {
-      setLogs(prev => [...prev, `关键错误: ${e}`]);
+      setLogs(prev => [...prev, `关键错误: ${errorMessage(e)}`]);
     } finally {
       setSyncing(false);
       // Progress will be handled by useEffect to jump to 100
@@ -92,7 +116,10 @@ export function Dashboard({
               <select
                 className="flex h-9 md:h-10 w-full items-center justify-between rounded-md border border-input bg-background px-2 md:px-3 py-2 text-xs md:text-sm ring-offset-background placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-ring focus:ring-offset-2 disabled:cursor-not-allowed disabled:opacity-50"
                 value={sourceId}
-                onChange={e => setSourceId(e.target.value)}
+                onChange={e => {
+                  setSourceId(e.target.value);
+                  setTargetIds(targetIds.filter(t => t !== e.target.value));
+                }}
               >
                 <option value="" disabled>请选择源编辑器...</option>
                 {ides.map(ide => (
@@ -101,17 +128,24 @@ export function Dashboard({
               </select>
             </div>
             <div className="space-y-2 min-w-0">
-              <label className="text-xs md:text-sm font-medium text-muted-foreground">目标 (Target) IDE</label>
-              <select
-                className="flex h-9 md:h-10 w-full items-center justify-between rounded-md border border-input bg-background px-2 md:px-3 py-2 text-xs md:text-sm ring-offset-background placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-ring focus:ring-offset-2 disabled:cursor-not-allowed disabled:opacity-50"
-                value={targetId}
-                onChange={e => setTargetId(e.target.value)}
-              >
-                <option value="" disabled>请选择目标编辑器...</option>
-                {ides.map(ide => (
-                  <option key={ide.id} value={ide.id}>{ide.name}</option>
+              <label className="text-xs md:text-sm font-medium text-muted-foreground">目标 (Target) IDE，可多选</label>
+              <div className="flex flex-wrap gap-2">
+                {ides.filter(ide => ide.id !== sourceId).map(ide => (
+                  <button
+                    key={ide.id}
+                    type="button"
+                    onClick={() => toggleTarget(ide.id)}
+                    className={cn(
+                      "h-9 md:h-10 rounded-md border px-3 text-xs md:text-sm transition-colors",
+                      targetIds.includes(ide.id)
+                        ? "border-primary bg-primary/10 text-primary"
+                        : "border-input bg-background text-muted-foreground hover:bg-accent"
+                    )}
+                  >
+                    {ide.name}
+                  </button>
                 ))}
-              </select>
+              </div>
             </div>
           </div>
         </CardContent>
@@ -140,6 +174,13 @@ export function Dashboard({
                 title="快捷键"
                 desc="同步 keybindings.json"
               />
+              <ToggleOption
+                active={options.sync_snippets}
+                onClick={() => setOptions({ ...options, sync_snippets: !options.sync_snippets })}
+                icon={<FileCode className="w-4 h-4" />}
+                title="代码片段"
+                desc="同步 snippets 目录"
+              />
               <ToggleOption
                 active={options.sync_extensions}
                 onClick={() => setOptions({ ...options, sync_extensions: !options.sync_extensions })}
@@ -158,7 +199,7 @@ export function Dashboard({
 
             <div className="space-y-2">
               <label className="text-xs md:text-sm font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70">合并策略</label>
-              <div className="grid grid-cols-1 sm:grid-cols-2 gap-3">
+              <div className="grid grid-cols-1 sm:grid-cols-3 gap-3">
                 <StrategyBtn
                   active={options.strategy === 'Merge'}
                   onClick={() => setOptions({ ...options, strategy: 'Merge' })}
@@ -172,13 +213,19 @@ export function Dashboard({
                   desc="完全替换目标配置，原有配置将丢失。"
                   variant="destructive"
                 />
+                <StrategyBtn
+                  active={options.strategy === 'Bidirectional'}
+                  onClick={() => setOptions({ ...options, strategy: 'Bidirectional' })}
+                  title="双向同步"
+                  desc="合并两边自上次同步以来的修改，冲突项保留各自的值。仅支持单个目标。"
+                />
               </div>
             </div>
 
             <Button
               className="w-full h-10 md:h-12 text-base md:text-lg"
               onClick={handleSync}
-              disabled={syncing || !sourceId || !targetId}
+              disabled={syncing || !sourceId || targetIds.length === 0}
             >
               {syncing ? "正在同步..." : "开始同步"}
             </Button>
@@ -195,7 +242,7 @@ export function Dashboard({
               <div className="text-muted-foreground italic opacity-50">等待操作...</div>
             ) : (
               logs.map((log, i) => (
-                <div key={i} className={cn("mb-1 break-all", log.includes("Error") && "text-red-400")}>
+                <div key={i} className={cn("mb-1 break-all", (log.includes("Error") || log.includes("Failed") || log.includes("出错") || log.includes("失败")) && "text-red-400")}>
                   <span className="opacity-50 mr-2">[{i + 1}]</span>
                   {log}
                 </div>
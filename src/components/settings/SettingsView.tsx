@@ -4,7 +4,7 @@ import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "../ui
 import { Button } from "../ui/button";
 import { Settings, Database, Shield, RefreshCw, CheckCircle, Trash2, RotateCcw } from "lucide-react";
 import { cn } from "../../lib/utils";
-import { AppSettings, DEFAULT_SETTINGS, IdeProfile, BackupInfo } from "../../types";
+import { AppSettings, DEFAULT_SETTINGS, IdeProfile, BackupInfo, RetentionConfig } from "../../types";
 
 interface SettingsViewProps {
   settings: AppSettings;
@@ -17,6 +17,25 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
   const [selectedIdeForBackup, setSelectedIdeForBackup] = useState<string>("");
   const [backups, setBackups] = useState<BackupInfo[]>([]);
   const [loadingBackups, setLoadingBackups] = useState(false);
+  // 备份保留数量即保留策略（retention.json）的全局 keep_last
+  const [retention, setRetention] = useState<RetentionConfig | null>(null);
+
+  useEffect(() => {
+    invoke<RetentionConfig>("get_retention_config")
+      .then(setRetention)
+      .catch((e) => console.error("Failed to load retention config:", e));
+  }, []);
+
+  const handleKeepLastChange = async (keepLast: number) => {
+    try {
+      const current = await invoke<RetentionConfig>("get_retention_config");
+      const config = { ...current, global: { ...current.global, keep_last: keepLast } };
+      await invoke("set_retention_config", { config });
+      setRetention(config);
+    } catch (e) {
+      console.error("Failed to save retention config:", e);
+    }
+  };
 
   // 加载备份列表
   const loadBackups = async (configPath: string) => {
@@ -68,7 +87,7 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
   }, [selectedIdeForBackup, ides]);
 
   const handleSave = () => {
-    // 设置已在 App.tsx 的 useEffect 中自动保存到 localStorage
+    // 设置在修改时已由 App.tsx 保存到 config.json
     setSaved(true);
     setTimeout(() => setSaved(false), 2000);
   };
@@ -116,17 +135,17 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
           <SettingItem
             label="启动时自动检测 IDE"
             description="应用启动时自动扫描并检测已安装的编辑器"
-            checked={settings.autoDetectOnStartup}
-            onChange={(checked) => setSettings({ ...settings, autoDetectOnStartup: checked })}
+            checked={settings.auto_detect_on_startup}
+            onChange={(checked) => setSettings({ ...settings, auto_detect_on_startup: checked })}
           />
           <div className="space-y-2">
             <label className="text-sm font-medium">默认同步策略</label>
             <div className="grid grid-cols-2 gap-3">
               <button
-                onClick={() => setSettings({ ...settings, defaultStrategy: "Merge" })}
+                onClick={() => setSettings({ ...settings, default_strategy: "Merge" })}
                 className={cn(
                   "p-3 rounded-md border text-left transition-all",
-                  settings.defaultStrategy === "Merge"
+                  settings.default_strategy === "Merge"
                     ? "border-primary bg-primary/5"
                     : "border-input hover:bg-accent"
                 )}
@@ -135,10 +154,10 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
                 <p className="text-xs text-muted-foreground">保留目标端独有设置</p>
               </button>
               <button
-                onClick={() => setSettings({ ...settings, defaultStrategy: "Overwrite" })}
+                onClick={() => setSettings({ ...settings, default_strategy: "Overwrite" })}
                 className={cn(
                   "p-3 rounded-md border text-left transition-all",
-                  settings.defaultStrategy === "Overwrite"
+                  settings.default_strategy === "Overwrite"
                     ? "border-destructive bg-destructive/10"
                     : "border-input hover:bg-accent"
                 )}
@@ -164,20 +183,20 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
           <SettingItem
             label="默认同步配置文件 (settings.json)"
             description="新建同步任务时默认勾选此项"
-            checked={settings.defaultSyncSettings}
-            onChange={(checked) => setSettings({ ...settings, defaultSyncSettings: checked })}
+            checked={settings.default_sync_settings}
+            onChange={(checked) => setSettings({ ...settings, default_sync_settings: checked })}
           />
           <SettingItem
             label="默认同步快捷键 (keybindings.json)"
             description="新建同步任务时默认勾选此项"
-            checked={settings.defaultSyncKeybindings}
-            onChange={(checked) => setSettings({ ...settings, defaultSyncKeybindings: checked })}
+            checked={settings.default_sync_keybindings}
+            onChange={(checked) => setSettings({ ...settings, default_sync_keybindings: checked })}
           />
           <SettingItem
             label="默认同步插件扩展"
             description="新建同步任务时默认勾选此项（同步较慢）"
-            checked={settings.defaultSyncExtensions}
-            onChange={(checked) => setSettings({ ...settings, defaultSyncExtensions: checked })}
+            checked={settings.default_sync_extensions}
+            onChange={(checked) => setSettings({ ...settings, default_sync_extensions: checked })}
           />
         </CardContent>
       </Card>
@@ -192,25 +211,20 @@ export function SettingsView({ settings, setSettings, ides }: SettingsViewProps)
           <CardDescription className="text-xs md:text-sm">配置自动备份策略和查看历史备份</CardDescription>
         </CardHeader>
         <CardContent className="p-4 md:p-6 pt-0 space-y-4">
-          <SettingItem
-            label="同步前自动备份"
-            description="在执行同步操作前自动备份目标配置"
-            checked={settings.autoBackup}
-            onChange={(checked) => setSettings({ ...settings, autoBackup: checked })}
-          />
           <div className="space-y-2">
             <label className="text-sm font-medium">备份保留数量</label>
             <select
               className="flex h-9 w-full items-center rounded-md border border-input bg-background px-3 py-2 text-sm"
-              value={settings.backupRetentionCount}
-              onChange={(e) => setSettings({ ...settings, backupRetentionCount: parseInt(e.target.value) })}
+              value={retention?.global.keep_last ?? 10}
+              disabled={!retention}
+              onChange={(e) => handleKeepLastChange(parseInt(e.target.value))}
             >
               <option value="3">最近 3 个备份</option>
               <option value="5">最近 5 个备份</option>
               <option value="10">最近 10 个备份</option>
               <option value="20">最近 20 个备份</option>
             </select>
-            <p className="text-xs text-muted-foreground">旧备份会自动删除</p>
+            <p className="text-xs text-muted-foreground">同步前总是备份目标配置，旧备份在同步后按保留策略自动删除</p>
           </div>
 
           {/* 备份列表 */}
@@ -1,57 +1,211 @@
 export interface IdeProfile {
   id: string;
   name: string;
-  ide_type: "VSCode" | "Cursor" | "Antigravity" | "Windsurf" | "Unknown";
+  ide_type: "VSCode" | "Cursor" | "Antigravity" | "Windsurf" | "Archive" | "Profile" | "Unknown";
   config_path: string;
   extensions_path: string;
   executable: string;
   is_detected: boolean;
 }
 
-export type SyncStrategy = "Overwrite" | "Merge";
+export type SyncStrategy = "Overwrite" | "Merge" | "Bidirectional";
 
 export interface SyncOptions {
   sync_settings: boolean;
   sync_extensions: boolean;
   sync_keybindings: boolean;
+  sync_snippets: boolean;
   dry_run: boolean;
   strategy: SyncStrategy;
 }
 
+// 同步时排除的内容；exclude_settings 中以 * 结尾的项按前缀匹配
+export interface SyncFilters {
+  exclude_settings: string[];
+  exclude_extensions: string[];
+  exclude_snippets: string[];
+}
+
+// 保存的同步组，新建时 id 可为空字符串
+export interface SyncGroup {
+  id: string;
+  name: string;
+  source_id: string;
+  target_ids: string[];
+  options: SyncOptions;
+  filters: SyncFilters;
+  // 源 IDE 的配置文件变化时自动运行
+  auto_sync: boolean;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface HistoryQuery {
+  ide?: string;
+  status?: "Success" | "Failed";
+  // 同步组 ID 或名称
+  group?: string;
+  trigger?: SyncTrigger;
+  // 定时任务 ID
+  job?: string;
+  from?: number;
+  to?: number;
+  offset?: number;
+  limit?: number;
+}
+
+export interface HistoryPage {
+  records: SyncRecord[];
+  total: number;
+}
+
+export type FileStatus = "Synced" | "DryRun" | "Skipped" | "Deleted" | "Failed" | "Unchanged";
+
+export interface FileOutcome {
+  file_type: string;
+  path: string;
+  status: FileStatus;
+  backup_path: string | null;
+  message: string;
+  error_code: ErrorCode | null;
+}
+
+// 双向同步中两边都修改过的设置项，key 为以 "/" 连接的路径
+export interface SyncConflict {
+  file_type: string;
+  file: string;
+  key: string;
+  source_value: unknown;
+  target_value: unknown;
+}
+
 export interface SyncResult {
+  record_id: string;
   success: boolean;
   log: string[];
+  snapshot_id: string | null;
+  installed_extensions: string[];
+  failed_extensions: string[];
+  files: FileOutcome[];
+  duration_ms: number;
+  conflicts: SyncConflict[];
+  // 双向同步时安装到源 IDE 的扩展
+  source_installed_extensions: string[];
 }
 
 export interface SyncRecord {
   id: string;
   timestamp: number;
   source_name: string;
+  source_id: string;
   target_name: string;
   status: string;
   details: string;
+  target_id: string;
+  snapshot_id: string | null;
+  installed_extensions: string[];
+  undone: boolean;
+  options: SyncOptions | null;
+  files: FileOutcome[];
+  failed_extensions: string[];
+  duration_ms: number;
+  // 完整日志，仅 get_history_entry 返回
+  log: string[];
+  // 一对多同步的各个目标；单目标记录为空
+  targets: TargetRecord[];
+  conflicts: SyncConflict[];
+  source_installed_extensions: string[];
+  // 由同步组触发时的组
+  group_id: string | null;
+  group_name: string | null;
+  trigger: SyncTrigger;
+  // 由定时任务触发时的任务
+  job_id: string | null;
 }
 
+// Auto：监听到源配置变化后自动运行；Scheduled：定时任务运行
+export type SyncTrigger = "Manual" | "Auto" | "Scheduled";
+
+// 自动同步完成后后端发出的 "auto-sync" 事件
+export interface AutoRun {
+  group_id: string;
+  group_name: string;
+  record_id: string | null;
+  success: boolean;
+  error: AppError | null;
+}
+
+// minutes 为间隔分钟数；expression 为 5 段（星期 0 和 7 为周日）或 6 段（带秒）的 cron 表达式，按本地时间
+export type JobSchedule = { Interval: { minutes: number } } | { Cron: { expression: string } };
+
+export type JobAction =
+  | { Sync: { source_id: string; target_ids: string[]; options: SyncOptions } }
+  | { Group: { group_id: string } }
+  | { Preset: { name: string } };
+
+// 定时任务，新建时 id 可为空字符串；last_* 由调度器写入
+export interface ScheduledJob {
+  id: string;
+  name: string;
+  schedule: JobSchedule;
+  action: JobAction;
+  enabled: boolean;
+  last_run: number | null;
+  last_success: boolean | null;
+  last_error: string | null;
+  created_at: number;
+  updated_at: number;
+}
+
+// next_run 早于当前时间表示错过的运行，调度器会尽快补跑
+export interface JobInfo {
+  job: ScheduledJob;
+  next_run: number | null;
+}
+
+// 定时任务运行后后端发出的 "scheduled-sync" 事件，run_scheduled_job 也返回它
+export interface JobRun {
+  job_id: string;
+  job_name: string;
+  record_id: string | null;
+  success: boolean;
+  error: AppError | null;
+}
+
+// 应用设置，保存在 ~/.ide-sync-pro/config.json，命令行也使用其中的默认选项
 export interface AppSettings {
-  autoDetectOnStartup: boolean;
-  defaultStrategy: SyncStrategy;
-  defaultSyncSettings: boolean;
-  defaultSyncKeybindings: boolean;
-  defaultSyncExtensions: boolean;
-  autoBackup: boolean;
-  backupRetentionCount: number;
+  auto_detect_on_startup: boolean;
+  default_strategy: SyncStrategy;
+  default_sync_settings: boolean;
+  default_sync_keybindings: boolean;
+  default_sync_extensions: boolean;
+  default_sync_snippets: boolean;
 }
 
 export const DEFAULT_SETTINGS: AppSettings = {
-  autoDetectOnStartup: true,
-  defaultStrategy: "Merge",
-  defaultSyncSettings: true,
-  defaultSyncKeybindings: true,
-  defaultSyncExtensions: false,
-  autoBackup: true,
-  backupRetentionCount: 10,
+  auto_detect_on_startup: true,
+  default_strategy: "Merge",
+  default_sync_settings: true,
+  default_sync_keybindings: true,
+  default_sync_extensions: false,
+  default_sync_snippets: false,
 };
 
+// 命名的同步预设，可用 run_sync_preset 按名称运行
+export interface SyncPreset {
+  name: string;
+  source_id: string;
+  target_id: string;
+  options: SyncOptions;
+}
+
+// get_config / set_config；version 由后端维护
+export interface AppConfig {
+  version: number;
+  settings: AppSettings;
+  presets: SyncPreset[];
+}
+
 // 备份相关类型
 export interface BackupMetadata {
   backup_path: string;
@@ -61,6 +215,10 @@ export interface BackupMetadata {
   target_ide: string;
   file_type: string;
   file_size: number;
+  content_hash: string;
+  stored_size: number;
+  reason: "sync" | "pre-restore";
+  record_id: string | null;
 }
 
 export interface BackupInfo {
@@ -68,3 +226,181 @@ export interface BackupInfo {
   formatted_time: string;
   size_mb: number;
 }
+
+export interface BackupStats {
+  total_count: number;
+  total_size_mb: number;
+  disk_size_mb: number;
+  oldest_backup: string | null;
+  newest_backup: string | null;
+}
+
+export interface BackupFilter {
+  ide?: string;
+  file_type?: string;
+  source_ide?: string;
+  from?: number;
+  to?: number;
+  offset?: number;
+  limit?: number;
+}
+
+export interface BackupPage {
+  backups: BackupInfo[];
+  total: number;
+  per_ide: { ide: string; stats: BackupStats }[];
+}
+
+export interface SnapshotFile {
+  original_path: string;
+  file_type: string;
+  backup_path: string | null;
+  synced_hash: string | null;
+  deleted: boolean;
+  ide: string | null;
+}
+
+export interface Snapshot {
+  id: string;
+  timestamp: number;
+  source_ide: string;
+  target_ide: string;
+  files: SnapshotFile[];
+  record_id: string | null;
+}
+
+export interface SnapshotInfo {
+  snapshot: Snapshot;
+  formatted_time: string;
+}
+
+export interface RetentionPolicy {
+  keep_last: number;
+  keep_days: number;
+  keep_daily: number;
+  keep_weekly: number;
+  keep_monthly: number;
+}
+
+export interface HistoryRetention {
+  keep_last: number;
+  keep_days: number;
+}
+
+export interface RetentionConfig {
+  global: RetentionPolicy;
+  per_ide: Record<string, RetentionPolicy>;
+  history: HistoryRetention;
+}
+
+export type DiffAgainst =
+  | { type: "current" }
+  | { type: "backup"; backup_path: string };
+
+export interface KeyChange {
+  key: string;
+  kind: "Added" | "Removed" | "Modified";
+  old_value: unknown;
+  new_value: unknown;
+}
+
+export interface BackupDiff {
+  file_type: string;
+  old_label: string;
+  new_label: string;
+  structured: boolean;
+  changes: KeyChange[];
+  unified: string;
+}
+
+export type OrphanKind =
+  | "MissingData"
+  | "UnreferencedData"
+  | "MissingSnapshotBackup"
+  | "LegacyMissingMetadata"
+  | "LegacyMissingData";
+
+export interface VerifyReport {
+  checked_count: number;
+  corrupt: { backup_path: string; code: ErrorCode; reason: string }[];
+  orphaned: { path: string; kind: OrphanKind }[];
+}
+
+export interface ArchiveManifest {
+  format_version: number;
+  app_version: string;
+  created_at: number;
+  source_ide: string;
+  snapshot_id: string | null;
+  files: string[];
+  extensions_captured: boolean;
+}
+
+export type ErrorCode =
+  | "ide_not_found"
+  | "file_not_found"
+  | "io_error"
+  | "invalid_path"
+  | "invalid_jsonc"
+  | "cli_not_found"
+  | "cli_failed"
+  | "lock_failed"
+  | "backup_not_found"
+  | "backup_corrupt"
+  | "snapshot_not_found"
+  | "snapshot_backup_missing"
+  | "modified_since_sync"
+  | "missing_checksum"
+  | "record_not_found"
+  | "already_undone"
+  | "invalid_archive"
+  | "unsupported_archive_version"
+  | "invalid_archive_entry"
+  | "unsupported_strategy"
+  | "group_not_found"
+  | "invalid_group_name"
+  | "invalid_group_targets"
+  | "job_not_found"
+  | "invalid_job_name"
+  | "invalid_job_targets"
+  | "invalid_schedule"
+  | "preset_not_found"
+  | "invalid_preset_name"
+  | "invalid_preset_targets"
+  | "serialization_error"
+  | "internal_error";
+
+// 分组历史记录中的单个目标
+export interface TargetRecord {
+  target_id: string;
+  target_name: string;
+  success: boolean;
+  snapshot_id: string | null;
+  installed_extensions: string[];
+}
+
+// 一对多同步中单个目标的结果
+export interface TargetResult {
+  target_id: string;
+  target_name: string;
+  result: SyncResult;
+}
+
+// 一对多同步的结果，所有目标共用一条历史记录
+export interface FanoutResult {
+  record_id: string;
+  success: boolean;
+  targets: TargetResult[];
+  duration_ms: number;
+}
+
+// 后端日志、错误和历史详情使用的语言
+export type Locale = "en" | "zh-CN";
+
+// 后端命令失败时 reject 的错误对象
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+  params: Record<string, string>;
+  path: string | null;
+}